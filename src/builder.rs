@@ -0,0 +1,208 @@
+use crate::clock::Clock;
+use crate::job::misfire::MisfirePolicy;
+use crate::job::to_code::{JobCode, NotificationCode};
+use crate::job_scheduler::JobsSchedulerLocked;
+use crate::registry::OrphanPolicy;
+use crate::simple::{
+    SimpleJobCode, SimpleMetadataStore, SimpleNotificationCode, SimpleNotificationStore,
+};
+use crate::store::{MetaDataStorage, NotificationStore};
+use crate::JobSchedulerError;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How many messages every receiver of the channels between the parts of the scheduler queues by
+/// default
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 200;
+
+///
+/// Builds a `JobScheduler`, checking the settings fit together at `build`. What isn't set keeps
+/// the default of `JobScheduler::new`.
+/// ```rust,ignore
+/// let sched = JobScheduler::builder()
+///     .metadata_storage(Box::new(metadata_storage))
+///     .notification_storage(Box::new(notification_storage))
+///     .polling_interval(Duration::from_secs(1))
+///     .max_concurrency(8)
+///     .concurrency_limit("reports", 2)
+///     .misfire_policy(MisfirePolicy::Skip)
+///     .build()
+///     .await?;
+/// ```
+#[derive(Default)]
+pub struct JobSchedulerBuilder {
+    polling_interval: Option<Duration>,
+    channel_capacity: Option<usize>,
+    metadata_storage: Option<Box<dyn MetaDataStorage + Send + Sync>>,
+    notification_storage: Option<Box<dyn NotificationStore + Send + Sync>>,
+    job_code: Option<Box<dyn JobCode + Send + Sync>>,
+    notification_code: Option<Box<dyn NotificationCode + Send + Sync>>,
+    max_blocking_jobs: Option<usize>,
+    max_concurrency: Option<usize>,
+    concurrency_limits: Vec<(String, usize)>,
+    misfire_policy: Option<MisfirePolicy>,
+    orphan_policy: Option<OrphanPolicy>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl JobSchedulerBuilder {
+    /// How long the scheduler sleeps at most before it checks the store for due jobs
+    pub fn polling_interval(mut self, polling_interval: Duration) -> Self {
+        self.polling_interval = Some(polling_interval);
+        self
+    }
+
+    /// How many messages every receiver of the channels between the parts of the scheduler queues,
+    /// like activations of jobs and notifications. Senders wait while a queue is full. Defaults to
+    /// `DEFAULT_CHANNEL_CAPACITY`.
+    pub fn channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.channel_capacity = Some(channel_capacity);
+        self
+    }
+
+    pub fn metadata_storage(
+        mut self,
+        metadata_storage: Box<dyn MetaDataStorage + Send + Sync>,
+    ) -> Self {
+        self.metadata_storage = Some(metadata_storage);
+        self
+    }
+
+    pub fn notification_storage(
+        mut self,
+        notification_storage: Box<dyn NotificationStore + Send + Sync>,
+    ) -> Self {
+        self.notification_storage = Some(notification_storage);
+        self
+    }
+
+    pub fn job_code(mut self, job_code: Box<dyn JobCode + Send + Sync>) -> Self {
+        self.job_code = Some(job_code);
+        self
+    }
+
+    pub fn notification_code(
+        mut self,
+        notification_code: Box<dyn NotificationCode + Send + Sync>,
+    ) -> Self {
+        self.notification_code = Some(notification_code);
+        self
+    }
+
+    /// Cap how many sync jobs run at once, see `JobScheduler::set_max_blocking_jobs`. It doesn't
+    /// size tokio's blocking thread pool
+    pub fn max_blocking_jobs(mut self, max_blocking_jobs: usize) -> Self {
+        self.max_blocking_jobs = Some(max_blocking_jobs);
+        self
+    }
+
+    /// How many jobs may run at the same time
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    /// How many jobs of a concurrency group may run at the same time
+    pub fn concurrency_limit(mut self, group: &str, limit: usize) -> Self {
+        self.concurrency_limits.push((group.to_string(), limit));
+        self
+    }
+
+    /// The misfire policy of jobs that are added without one
+    pub fn misfire_policy(mut self, misfire_policy: MisfirePolicy) -> Self {
+        self.misfire_policy = Some(misfire_policy);
+        self
+    }
+
+    /// What the scheduler does with the jobs in the metadata store that have no code when it's
+    /// initialized, defaults to `OrphanPolicy::Report`
+    pub fn orphan_policy(mut self, orphan_policy: OrphanPolicy) -> Self {
+        self.orphan_policy = Some(orphan_policy);
+        self
+    }
+
+    pub fn clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    fn validate(&self) -> Result<(), JobSchedulerError> {
+        if self.polling_interval.is_some_and(|p| p.is_zero()) {
+            return Err(JobSchedulerError::InvalidPollingInterval);
+        }
+        // The queue of a receiver can't be empty, or hold more than a semaphore has permits
+        if self
+            .channel_capacity
+            .is_some_and(|c| c == 0 || c > tokio::sync::Semaphore::MAX_PERMITS)
+        {
+            return Err(JobSchedulerError::InvalidChannelCapacity);
+        }
+        if self.max_blocking_jobs == Some(0) {
+            return Err(JobSchedulerError::InvalidMaxBlockingJobs);
+        }
+        if self.max_concurrency == Some(0) {
+            return Err(JobSchedulerError::InvalidConcurrencyLimit);
+        }
+        let mut groups = HashSet::new();
+        for (group, limit) in self.concurrency_limits.iter() {
+            // A group can't run more jobs than the scheduler
+            let over_max = self.max_concurrency.is_some_and(|max| *limit > max);
+            if *limit == 0 || over_max || !groups.insert(group) {
+                return Err(JobSchedulerError::InvalidConcurrencyLimit);
+            }
+        }
+        if let Some(MisfirePolicy::FireAll { max_runs: 0 }) = self.misfire_policy {
+            return Err(JobSchedulerError::InvalidMisfirePolicy);
+        }
+        Ok(())
+    }
+
+    /// Build the scheduler, fails when a setting is out of range or doesn't fit with the others
+    pub async fn build(self) -> Result<JobsSchedulerLocked, JobSchedulerError> {
+        self.validate()?;
+        let JobSchedulerBuilder {
+            polling_interval,
+            channel_capacity,
+            metadata_storage,
+            notification_storage,
+            job_code,
+            notification_code,
+            max_blocking_jobs,
+            max_concurrency,
+            concurrency_limits,
+            misfire_policy,
+            orphan_policy,
+            clock,
+        } = self;
+
+        let mut sched = JobsSchedulerLocked::new_with_options(
+            metadata_storage.unwrap_or_else(|| Box::new(SimpleMetadataStore::default())),
+            notification_storage.unwrap_or_else(|| Box::new(SimpleNotificationStore::default())),
+            job_code.unwrap_or_else(|| Box::new(SimpleJobCode::default())),
+            notification_code.unwrap_or_else(|| Box::new(SimpleNotificationCode::default())),
+            channel_capacity.unwrap_or(DEFAULT_CHANNEL_CAPACITY),
+            clock,
+        )
+        .await?;
+        if let Some(misfire_policy) = misfire_policy {
+            sched.default_misfire_policy = misfire_policy;
+        }
+        if let Some(orphan_policy) = orphan_policy {
+            sched.orphan_policy = orphan_policy;
+        }
+        if let Some(polling_interval) = polling_interval {
+            sched.set_polling_interval(polling_interval).await?;
+        }
+        if let Some(max_blocking_jobs) = max_blocking_jobs {
+            sched.set_max_blocking_jobs(max_blocking_jobs).await?;
+        }
+        if let Some(max_concurrency) = max_concurrency {
+            sched.set_max_concurrency(max_concurrency).await?;
+        }
+        for (group, limit) in concurrency_limits {
+            sched.set_concurrency_limit(&group, limit).await?;
+        }
+        Ok(sched)
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::{self, Receiver};
+
+type Filter<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+struct Subscriber<T> {
+    tx: mpsc::Sender<T>,
+    filter: Option<Filter<T>>,
+}
+
+///
+/// A channel that hands every value to all of its receivers. Every receiver has a bounded queue
+/// of its own, and sending waits while the queue of a receiver is full. A burst slows the sender
+/// down instead of values getting lost, like they do when a broadcast receiver lags behind.
+pub struct FanOut<T> {
+    subscribers: Arc<Mutex<Vec<Subscriber<T>>>>,
+    capacity: usize,
+}
+
+impl<T> Clone for FanOut<T> {
+    fn clone(&self) -> Self {
+        Self {
+            subscribers: self.subscribers.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<T> FanOut<T>
+where
+    T: Clone + Send + 'static,
+{
+    ///
+    /// Create a channel of which every receiver queues up to `capacity` values, which has to be
+    /// more than 0
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            capacity,
+        }
+    }
+
+    fn add_subscriber(&self, filter: Option<Filter<T>>) -> Receiver<T> {
+        let (tx, rx) = mpsc::channel(self.capacity);
+        let mut w = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        w.push(Subscriber { tx, filter });
+        rx
+    }
+
+    ///
+    /// A receiver of the values that are sent from now on
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.add_subscriber(None)
+    }
+
+    ///
+    /// A receiver of the values sent from now on that pass the filter, for waiting on an answer
+    /// without queueing up the answers to everyone else
+    pub fn subscribe_filtered<F>(&self, filter: F) -> Receiver<T>
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.add_subscriber(Some(Box::new(filter)))
+    }
+
+    ///
+    /// Send the value to the receivers that want it, waiting for room in their queues. Returns
+    /// how many receivers got it, and fails when none did.
+    pub async fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let txs = {
+            let mut w = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+            w.retain(|subscriber| !subscriber.tx.is_closed());
+            w.iter()
+                .filter(|subscriber| match &subscriber.filter {
+                    Some(filter) => filter(&value),
+                    None => true,
+                })
+                .map(|subscriber| subscriber.tx.clone())
+                .collect::<Vec<_>>()
+        };
+        let mut sent = 0;
+        for tx in txs {
+            if tx.send(value.clone()).await.is_ok() {
+                sent += 1;
+            }
+        }
+        if sent == 0 {
+            return Err(SendError(value));
+        }
+        Ok(sent)
+    }
+
+    ///
+    /// Whether every receiver took all the values that were sent to it
+    pub fn is_empty(&self) -> bool {
+        let r = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        r.iter().all(|subscriber| {
+            subscriber.tx.is_closed() || subscriber.tx.capacity() == subscriber.tx.max_capacity()
+        })
+    }
+}
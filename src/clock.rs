@@ -0,0 +1,20 @@
+use chrono::{DateTime, Utc};
+
+///
+/// Where the scheduler gets the time from when it works out which jobs are due, and when runs
+/// and retries happen. The first tick of a job is worked out with it when the job is added. The
+/// scheduler still sleeps in real time, at most the polling interval at once.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+///
+/// The clock of the system, the default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
@@ -1,30 +1,222 @@
+use crate::builder::DEFAULT_CHANNEL_CAPACITY;
+use crate::channel::FanOut;
+use crate::clock::{Clock, SystemClock};
+use crate::job::job_context::SharedState;
 #[cfg(not(feature = "has_bytes"))]
 use crate::job::job_data::{JobState, NotificationData};
 #[cfg(feature = "has_bytes")]
 use crate::job::job_data_prost::{JobState, NotificationData};
 use crate::job::to_code::{JobCode, NotificationCode};
-use crate::job::{JobToRunAsync, NotificationId};
+use crate::job::{JobToRunFallibleAsync, NotificationId};
+use crate::notification::JobNotificationDetails;
 use crate::store::{MetaDataStorage, NotificationStore};
 use crate::{JobSchedulerError, JobStoredData, OnJobNotification};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
-use tokio::sync::broadcast::Sender;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::{Notify, RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::task_tracker::TaskTrackerToken;
+use tokio_util::task::TaskTracker;
 use uuid::Uuid;
 
 pub type NotificationDeletedResult =
     Result<(Uuid, bool, Option<Vec<JobState>>), (JobSchedulerError, Option<NotificationId>)>;
 
+///
+/// A job that is due to run
+#[derive(Clone, Copy, Debug)]
+pub struct JobActivation {
+    pub job_id: Uuid,
+    /// When the run was due
+    pub scheduled_at: DateTime<Utc>,
+    /// Whether the run was requested with `JobScheduler::run_now` instead of by the schedule
+    pub manual: bool,
+}
+
+///
+/// Counts the activations and notifications of every job that were sent but weren't handled yet.
+/// A job that is done isn't removed while it still has some, or its run wouldn't find its code
+/// and its notifications wouldn't find their handlers.
+#[derive(Clone, Default)]
+pub struct PendingWork {
+    counts: Arc<std::sync::Mutex<HashMap<Uuid, isize>>>,
+}
+
+impl PendingWork {
+    // A receiver can take a value before the count of its sender went up, so a count can go
+    // below 0 for a moment
+    fn change(&self, job_id: Uuid, by: isize) {
+        let mut w = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        let count = w.entry(job_id).or_default();
+        *count += by;
+        if *count == 0 {
+            w.remove(&job_id);
+        }
+    }
+
+    /// Sends a value about the job, which is counted once for every receiver that got it
+    pub async fn send<T>(
+        &self,
+        job_id: Uuid,
+        tx: &FanOut<T>,
+        value: T,
+    ) -> Result<usize, SendError<T>>
+    where
+        T: Clone + Send + 'static,
+    {
+        self.change(job_id, 1);
+        let sent = tx.send(value).await;
+        match sent {
+            Ok(receivers) => self.change(job_id, receivers as isize - 1),
+            Err(_) => self.change(job_id, -1),
+        }
+        sent
+    }
+
+    /// Stops counting a received value once the returned guard is dropped
+    pub fn take(&self, job_id: Uuid) -> PendingJob {
+        PendingJob {
+            pending: self.clone(),
+            job_id,
+        }
+    }
+
+    /// Forgets all counts, for when the channels were dropped with what was in them
+    pub fn clear(&self) {
+        let mut w = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        w.clear();
+    }
+
+    /// Whether values about the job weren't handled yet
+    pub fn contains(&self, job_id: &Uuid) -> bool {
+        let r = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        r.get(job_id).is_some_and(|count| *count > 0)
+    }
+}
+
+///
+/// A received value about a job that is counted until it is dropped
+pub struct PendingJob {
+    pending: PendingWork,
+    job_id: Uuid,
+}
+
+impl Drop for PendingJob {
+    fn drop(&mut self) {
+        self.pending.change(self.job_id, -1);
+    }
+}
+
+///
+/// The tasks the scheduler spawns. Runs of jobs and notifications are waited for by a graceful
+/// shutdown, the tasks that listen on the channels are stopped after them.
+#[derive(Clone, Default)]
+pub struct BackgroundTasks {
+    runs: TaskTracker,
+    listeners: TaskTracker,
+    stop: Arc<std::sync::RwLock<CancellationToken>>,
+}
+
+impl BackgroundTasks {
+    fn stop_token(&self) -> CancellationToken {
+        let r = self.stop.read().unwrap_or_else(|e| e.into_inner());
+        r.clone()
+    }
+
+    /// Spawns a task that listens on a channel until the tasks are stopped
+    pub fn spawn_listener<F>(&self, listener: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let stop = self.stop_token();
+        self.listeners.spawn(async move {
+            stop.run_until_cancelled(listener).await;
+        });
+    }
+
+    /// Spawns a run of a job or a notification, it is dropped when the tasks are stopped
+    pub fn spawn_run<F>(&self, run: F) -> JoinHandle<()>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let stop = self.stop_token();
+        self.runs.spawn(async move {
+            stop.run_until_cancelled(run).await;
+        })
+    }
+
+    /// Counts as a run until it is dropped, for work that is about to spawn a run
+    pub fn busy(&self) -> TaskTrackerToken {
+        self.runs.token()
+    }
+
+    /// How many runs are going
+    pub fn running(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Waits until the runs are done, and the activations and notifications in the channels were
+    /// handled
+    pub async fn drain(&self, context: &Context) {
+        self.runs.close();
+        loop {
+            let pending = !context.job_activation_tx.is_empty() || !context.notify_tx.is_empty();
+            self.runs.wait().await;
+            if !pending && context.job_activation_tx.is_empty() && context.notify_tx.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    /// Drops the runs that are still going and stops the listeners
+    pub async fn stop(&self) {
+        self.stop_token().cancel();
+        self.runs.close();
+        self.listeners.close();
+        self.runs.wait().await;
+        self.listeners.wait().await;
+    }
+
+    /// Gets ready to spawn tasks again after they were stopped
+    pub fn reopen(&self) {
+        {
+            let mut w = self.stop.write().unwrap_or_else(|e| e.into_inner());
+            *w = CancellationToken::new();
+        }
+        self.runs.reopen();
+        self.listeners.reopen();
+    }
+}
+
 pub struct Context {
-    pub job_activation_tx: Sender<Uuid>,
-    pub notify_tx: Sender<(Uuid, JobState)>,
-    pub job_create_tx: Sender<(JobStoredData, Arc<RwLock<Box<JobToRunAsync>>>)>,
-    pub job_created_tx: Sender<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
-    pub job_delete_tx: Sender<Uuid>,
-    pub job_deleted_tx: Sender<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
-    pub notify_create_tx: Sender<(NotificationData, Arc<RwLock<Box<OnJobNotification>>>)>,
-    pub notify_created_tx: Sender<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
-    pub notify_delete_tx: Sender<(Uuid, Option<Vec<JobState>>)>,
-    pub notify_deleted_tx: Sender<NotificationDeletedResult>,
+    pub job_activation_tx: FanOut<JobActivation>,
+    /// The activations and notifications that weren't handled yet
+    pub pending_work: PendingWork,
+    pub notify_tx: FanOut<(Uuid, JobState, JobNotificationDetails)>,
+    pub job_create_tx: FanOut<(JobStoredData, Arc<RwLock<Box<JobToRunFallibleAsync>>>)>,
+    pub job_created_tx: FanOut<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+    pub job_delete_tx: FanOut<Uuid>,
+    pub job_deleted_tx: FanOut<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+    pub notify_create_tx: FanOut<(NotificationData, Arc<RwLock<Box<OnJobNotification>>>)>,
+    pub notify_created_tx: FanOut<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+    pub notify_delete_tx: FanOut<(Uuid, Option<Vec<JobState>>)>,
+    pub notify_deleted_tx: FanOut<NotificationDeletedResult>,
+    /// Woken when jobs were added or removed, so the scheduler can recalculate when to tick
+    pub jobs_changed: Arc<Notify>,
+    /// Caps how many sync jobs run at once, no cap when `None`. The blocking pool isn't sized by it
+    pub blocking_jobs: Arc<RwLock<Option<Arc<Semaphore>>>>,
+    /// The state jobs get through their `JobContext`
+    pub state: SharedState,
+    /// The tasks the scheduler spawned
+    pub tasks: BackgroundTasks,
+    /// Where the scheduler gets the time from
+    pub clock: Arc<dyn Clock>,
     // TODO need to add when notification was deleted and there's no more references to it
     pub metadata_storage: Arc<RwLock<Box<dyn MetaDataStorage + Send + Sync>>>,
     pub notification_storage: Arc<RwLock<Box<dyn NotificationStore + Send + Sync>>>,
@@ -39,19 +231,39 @@ impl Context {
         job_code: Arc<RwLock<Box<dyn JobCode + Send + Sync>>>,
         notification_code: Arc<RwLock<Box<dyn NotificationCode + Send + Sync>>>,
     ) -> Self {
-        let (job_activation_tx, _job_activation_rx) = tokio::sync::broadcast::channel(200);
-        let (notify_tx, _notify_rx) = tokio::sync::broadcast::channel(200);
-        let (job_create_tx, _job_create_rx) = tokio::sync::broadcast::channel(200);
-        let (job_created_tx, _job_created_rx) = tokio::sync::broadcast::channel(200);
-        let (job_delete_tx, _job_delete_rx) = tokio::sync::broadcast::channel(200);
-        let (job_deleted_tx, _job_deleted_rx) = tokio::sync::broadcast::channel(200);
-        let (notify_create_tx, _notify_create_rx) = tokio::sync::broadcast::channel(200);
-        let (notify_created_tx, _notify_created_rx) = tokio::sync::broadcast::channel(200);
-        let (notify_delete_tx, _notify_delete_rx) = tokio::sync::broadcast::channel(200);
-        let (notify_deleted_tx, _notify_deleted_rx) = tokio::sync::broadcast::channel(200);
+        Self::new_with_channel_capacity(
+            metadata_storage,
+            notification_storage,
+            job_code,
+            notification_code,
+            DEFAULT_CHANNEL_CAPACITY,
+        )
+    }
+
+    ///
+    /// Create a context with channels of which every receiver queues `channel_capacity` messages,
+    /// which has to be more than 0
+    pub fn new_with_channel_capacity(
+        metadata_storage: Arc<RwLock<Box<dyn MetaDataStorage + Send + Sync>>>,
+        notification_storage: Arc<RwLock<Box<dyn NotificationStore + Send + Sync>>>,
+        job_code: Arc<RwLock<Box<dyn JobCode + Send + Sync>>>,
+        notification_code: Arc<RwLock<Box<dyn NotificationCode + Send + Sync>>>,
+        channel_capacity: usize,
+    ) -> Self {
+        let job_activation_tx = FanOut::new(channel_capacity);
+        let notify_tx = FanOut::new(channel_capacity);
+        let job_create_tx = FanOut::new(channel_capacity);
+        let job_created_tx = FanOut::new(channel_capacity);
+        let job_delete_tx = FanOut::new(channel_capacity);
+        let job_deleted_tx = FanOut::new(channel_capacity);
+        let notify_create_tx = FanOut::new(channel_capacity);
+        let notify_created_tx = FanOut::new(channel_capacity);
+        let notify_delete_tx = FanOut::new(channel_capacity);
+        let notify_deleted_tx = FanOut::new(channel_capacity);
 
         Self {
             job_activation_tx,
+            pending_work: Default::default(),
             notify_tx,
             job_create_tx,
             job_created_tx,
@@ -61,6 +273,11 @@ impl Context {
             notify_created_tx,
             notify_delete_tx,
             notify_deleted_tx,
+            jobs_changed: Arc::new(Notify::new()),
+            blocking_jobs: Arc::new(RwLock::new(None)),
+            state: Default::default(),
+            tasks: Default::default(),
+            clock: Arc::new(SystemClock),
             metadata_storage,
             notification_storage,
             job_code,
@@ -73,6 +290,7 @@ impl Clone for Context {
     fn clone(&self) -> Self {
         Self {
             job_activation_tx: self.job_activation_tx.clone(),
+            pending_work: self.pending_work.clone(),
             notify_tx: self.notify_tx.clone(),
             job_create_tx: self.job_create_tx.clone(),
             job_created_tx: self.job_created_tx.clone(),
@@ -82,6 +300,11 @@ impl Clone for Context {
             notify_created_tx: self.notify_created_tx.clone(),
             notify_delete_tx: self.notify_delete_tx.clone(),
             notify_deleted_tx: self.notify_deleted_tx.clone(),
+            jobs_changed: self.jobs_changed.clone(),
+            blocking_jobs: self.blocking_jobs.clone(),
+            state: self.state.clone(),
+            tasks: self.tasks.clone(),
+            clock: self.clock.clone(),
             metadata_storage: self.metadata_storage.clone(),
             notification_storage: self.notification_storage.clone(),
             job_code: self.job_code.clone(),
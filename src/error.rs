@@ -25,8 +25,19 @@ pub enum JobSchedulerError {
     NoNextTick,
     CantListGuids,
     CantListNextTicks,
+    CantListJobs,
     NotifyOnStateError,
     ParseSchedule,
+    InvalidPollingInterval,
+    InvalidMaxBlockingJobs,
+    InvalidConcurrencyLimit,
+    InvalidChannelCapacity,
+    InvalidMisfirePolicy,
+    CantRunNow,
+    CantChangeId,
+    WrongJobType,
+    #[cfg(feature = "serde")]
+    Payload(String),
     #[cfg(feature = "nats_storage")]
     BuilderNeedsField(String),
     #[cfg(feature = "nats_storage")]
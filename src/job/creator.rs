@@ -1,12 +1,16 @@
+use crate::channel::FanOut;
 use crate::context::Context;
-use crate::job::{JobLocked, JobToRunAsync};
+use crate::job::job_context::JobContext;
+use crate::job::{JobLocked, JobResult, JobToRunFallibleAsync};
+use crate::job_scheduler::JobsSchedulerLocked;
 use crate::store::MetaDataStorage;
 use crate::{JobSchedulerError, JobStoredData};
 use std::future::Future;
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::broadcast::{Receiver, Sender};
-use tokio::sync::RwLock;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::{Mutex, Notify, RwLock};
 use tracing::error;
 use uuid::Uuid;
 
@@ -16,20 +20,18 @@ pub struct JobCreator {}
 impl JobCreator {
     async fn listen_to_additions(
         storage: Arc<RwLock<Box<dyn MetaDataStorage + Send + Sync>>>,
-        mut rx: Receiver<(JobStoredData, Arc<RwLock<Box<JobToRunAsync>>>)>,
-        tx_created: Sender<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+        mut rx: Receiver<(JobStoredData, Arc<RwLock<Box<JobToRunFallibleAsync>>>)>,
+        tx_created: FanOut<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+        jobs_changed: Arc<Notify>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving {:?}", e);
-                break;
-            }
-            let (data, _) = val.unwrap();
+        while let Some((data, _)) = rx.recv().await {
             let uuid: Uuid = match data.id.as_ref().map(|b| b.into()) {
                 Some(uuid) => uuid,
                 None => {
-                    if let Err(e) = tx_created.send(Err((JobSchedulerError::CantAdd, None))) {
+                    if let Err(e) = tx_created
+                        .send(Err((JobSchedulerError::CantAdd, None)))
+                        .await
+                    {
                         error!("Error sending creation error {:?}", e);
                     }
                     continue;
@@ -40,13 +42,14 @@ impl JobCreator {
                 let saved = storage.add_or_update(data).await;
                 if let Err(e) = saved {
                     error!("Error saving job metadata {:?}", e);
-                    if let Err(e) = tx_created.send(Err((e, Some(uuid)))) {
+                    if let Err(e) = tx_created.send(Err((e, Some(uuid)))).await {
                         error!("Could not send failure {:?}", e);
                     }
                     continue;
                 }
             }
-            if let Err(e) = tx_created.send(Ok(uuid)) {
+            jobs_changed.notify_one();
+            if let Err(e) = tx_created.send(Ok(uuid)).await {
                 error!("Error sending created job {:?}", e);
             }
         }
@@ -59,68 +62,108 @@ impl JobCreator {
         let rx = context.job_create_tx.subscribe();
         let tx_created = context.job_created_tx.clone();
         let storage = context.metadata_storage.clone();
+        let jobs_changed = context.jobs_changed.clone();
+        let tasks = context.tasks.clone();
 
         Box::pin(async move {
-            tokio::spawn(JobCreator::listen_to_additions(storage, rx, tx_created));
+            tasks.spawn_listener(JobCreator::listen_to_additions(
+                storage,
+                rx,
+                tx_created,
+                jobs_changed,
+            ));
             Ok(())
         })
     }
 
+    ///
+    /// Starts a run of the job. A sync job runs here, a panic in it is passed on once the lock is
+    /// released so the job isn't poisoned for its next runs.
+    fn start_run(
+        job: &JobLocked,
+        job_scheduler: JobsSchedulerLocked,
+    ) -> Pin<Box<dyn Future<Output = JobResult> + Send>> {
+        let w = job.0.write();
+        if let Err(e) = w {
+            error!("Error getting job {:?}", e);
+            let e = format!("Error getting job {:?}", e);
+            return Box::pin(std::future::ready(Err(e.into())));
+        }
+        let mut w = w.unwrap();
+        let run = std::panic::catch_unwind(AssertUnwindSafe(|| w.run(job_scheduler)));
+        drop(w);
+        match run {
+            Ok(run) => run,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
     pub async fn add(context: &Context, mut job: JobLocked) -> Result<Uuid, JobSchedulerError> {
         let tx = context.job_create_tx.clone();
-        let mut rx = context.job_created_tx.subscribe();
-
         let data = job.job_data();
         let uuid = job.guid();
+        let mut rx = context.job_created_tx.subscribe_filtered(
+            move |created| matches!(created, Ok(id) | Err((_, Some(id))) if *id == uuid),
+        );
 
         if let Err(e) = data {
             error!("Error getting job data {e:?}");
             return Err(e);
         }
         let data = data.unwrap();
-        let job: Box<JobToRunAsync> = Box::new(move |job_id, job_scheduler| {
+        let blocking_jobs = context.blocking_jobs.clone();
+        let is_sync = job.0.read().map(|r| r.is_sync()).unwrap_or_default();
+        let sync_turn = Arc::new(Mutex::new(()));
+        let job: Box<JobToRunFallibleAsync> = Box::new(move |_job_id, job_scheduler| {
             let job = job.clone();
+            let blocking_jobs = blocking_jobs.clone();
+            let sync_turn = sync_turn.clone();
             Box::pin(async move {
-                let job_done = {
-                    let w = job.0.write();
-                    if let Err(e) = w {
-                        error!("Error getting job {:?}", e);
-                        return;
-                    }
-                    let mut w = w.unwrap();
-                    w.run(job_scheduler)
+                if !is_sync {
+                    return JobCreator::start_run(&job, job_scheduler).await;
+                }
+
+                // Runs of the same sync job take turns before taking up a place under the cap
+                let turn = sync_turn.lock_owned().await;
+                let semaphore = blocking_jobs.read().await.clone();
+                let permit = match semaphore {
+                    Some(semaphore) => semaphore.acquire_owned().await.ok(),
+                    None => None,
                 };
-                let job_done = job_done.await;
-                match job_done {
-                    Err(e) => {
-                        error!("Error running job {:?} {:?}", job_id, e);
-                    }
-                    Ok(val) => {
-                        if !val {
-                            error!("Error running job {:?}", job_id);
+                // The context is task local, the blocking thread gets its own copy
+                let context = JobContext::current();
+                // The thread keeps the turn and the place until it's done, also when the run
+                // timed out or was cancelled and isn't waited for anymore
+                let run = tokio::task::spawn_blocking(move || {
+                    let _held = (turn, permit);
+                    match context {
+                        Some(context) => {
+                            context.sync_scope(|| JobCreator::start_run(&job, job_scheduler))
                         }
+                        None => JobCreator::start_run(&job, job_scheduler),
                     }
+                });
+                match run.await {
+                    Ok(job_done) => job_done.await,
+                    Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                    Err(e) => Err(e.into()),
                 }
             })
         });
 
         let job = Arc::new(RwLock::new(job));
-        if let Err(_e) = tx.send((data, job)) {
+        if let Err(_e) = tx.send((data, job)).await {
             error!("Error sending new job");
             return Err(JobSchedulerError::CantAdd);
         }
 
-        while let Ok(val) = rx.recv().await {
+        while let Some(val) = rx.recv().await {
             match val {
-                Ok(ret_uuid) => {
-                    if ret_uuid == uuid {
-                        return Ok(uuid);
-                    }
+                Ok(ret_uuid) if ret_uuid == uuid => {
+                    return Ok(uuid);
                 }
-                Err((e, Some(ret_uuid))) => {
-                    if ret_uuid == uuid {
-                        return Err(e);
-                    }
+                Err((e, Some(ret_uuid))) if ret_uuid == uuid => {
+                    return Err(e);
                 }
                 _ => {}
             }
@@ -2,19 +2,20 @@
 use crate::job::job_data::{JobStoredData, JobType};
 #[cfg(feature = "has_bytes")]
 use crate::job::job_data_prost::{JobStoredData, JobType};
-use crate::job::{Job, JobToRunAsync};
-use crate::{JobScheduler, JobSchedulerError, JobToRun};
+use crate::job::{Job, JobResult, JobRun};
+use crate::{JobScheduler, JobSchedulerError};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use cron::Schedule;
-use tokio::sync::oneshot::Receiver;
-use tracing::error;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
 use uuid::Uuid;
 
 pub struct CronJob {
     pub data: JobStoredData,
-    pub run: Box<JobToRun>,
-    pub run_async: Box<JobToRunAsync>,
-    pub async_job: bool,
+    pub run: JobRun,
+    pub has_notifications: bool,
 }
 
 impl Job for CronJob {
@@ -22,11 +23,19 @@ impl Job for CronJob {
         true
     }
 
+    fn is_sync(&self) -> bool {
+        matches!(self.run, JobRun::Sync(_))
+    }
+
     fn schedule(&self) -> Option<Schedule> {
         self.data.schedule()
     }
 
-    fn repeated_every(&self) -> Option<u64> {
+    fn time_zone(&self) -> Option<Tz> {
+        self.data.time_zone()
+    }
+
+    fn repeated_every(&self) -> Option<Duration> {
         None
     }
 
@@ -66,6 +75,10 @@ impl Job for CronJob {
         self.data.id.as_ref().cloned().map(|e| e.into()).unwrap()
     }
 
+    fn set_job_id(&mut self, id: Uuid) {
+        self.data.id = Some(id.into());
+    }
+
     fn job_type(&self) -> JobType {
         JobType::Cron
     }
@@ -90,6 +103,14 @@ impl Job for CronJob {
         self.data.stopped = false;
     }
 
+    fn has_notifications(&self) -> bool {
+        self.has_notifications
+    }
+
+    fn set_has_notifications(&mut self) {
+        self.has_notifications = true;
+    }
+
     fn job_data_from_job(&mut self) -> Result<Option<JobStoredData>, JobSchedulerError> {
         Ok(Some(self.data.clone()))
     }
@@ -99,24 +120,8 @@ impl Job for CronJob {
         Ok(())
     }
 
-    fn run(&mut self, jobs: JobScheduler) -> Receiver<bool> {
-        let (tx, rx) = tokio::sync::oneshot::channel();
+    fn run(&mut self, jobs: JobScheduler) -> Pin<Box<dyn Future<Output = JobResult> + Send>> {
         let job_id = self.job_id();
-
-        if !self.async_job {
-            (self.run)(job_id, jobs);
-            if let Err(e) = tx.send(true) {
-                error!("Error notifying done {:?}", e);
-            }
-        } else {
-            let future = (self.run_async)(job_id, jobs);
-            tokio::task::spawn(async move {
-                future.await;
-                if let Err(e) = tx.send(true) {
-                    error!("Error notifying done {:?}", e);
-                }
-            });
-        }
-        rx
+        self.run.call(job_id, jobs)
     }
 }
@@ -1,11 +1,12 @@
+use crate::channel::FanOut;
 use crate::context::Context;
 use crate::store::MetaDataStorage;
 use crate::JobSchedulerError;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::broadcast::{Receiver, Sender};
-use tokio::sync::RwLock;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::{Notify, RwLock};
 use tracing::error;
 use uuid::Uuid;
 
@@ -16,27 +17,23 @@ impl JobDeleter {
     async fn listen_to_removals(
         storage: Arc<RwLock<Box<dyn MetaDataStorage + Send + Sync>>>,
         mut rx: Receiver<Uuid>,
-        tx_deleted: Sender<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+        tx_deleted: FanOut<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+        jobs_changed: Arc<Notify>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving value {:?}", e);
-                break;
-            }
-            let uuid = val.unwrap();
+        while let Some(uuid) = rx.recv().await {
             {
                 let mut storage = storage.write().await;
                 let delete = storage.delete(uuid).await;
                 if let Err(e) = delete {
                     error!("Error deleting {:?}", e);
-                    if let Err(e) = tx_deleted.send(Err((e, Some(uuid)))) {
+                    if let Err(e) = tx_deleted.send(Err((e, Some(uuid)))).await {
                         error!("Error sending delete error {:?}", e);
                     }
                     continue;
                 }
             }
-            if let Err(e) = tx_deleted.send(Ok(uuid)) {
+            jobs_changed.notify_one();
+            if let Err(e) = tx_deleted.send(Ok(uuid)).await {
                 error!("Error sending error {:?}", e);
             }
         }
@@ -49,24 +46,33 @@ impl JobDeleter {
         let rx = context.job_delete_tx.subscribe();
         let tx_deleted = context.job_deleted_tx.clone();
         let storage = context.metadata_storage.clone();
+        let jobs_changed = context.jobs_changed.clone();
+        let tasks = context.tasks.clone();
 
         Box::pin(async move {
-            tokio::spawn(JobDeleter::listen_to_removals(storage, rx, tx_deleted));
+            tasks.spawn_listener(JobDeleter::listen_to_removals(
+                storage,
+                rx,
+                tx_deleted,
+                jobs_changed,
+            ));
             Ok(())
         })
     }
 
     pub async fn remove(context: &Context, job_id: &Uuid) -> Result<(), JobSchedulerError> {
         let delete = context.job_delete_tx.clone();
-        let mut deleted = context.job_deleted_tx.subscribe();
-
         let job_id = *job_id;
-        tokio::spawn(async move {
-            if let Err(e) = delete.send(job_id) {
+        let mut deleted = context.job_deleted_tx.subscribe_filtered(
+            move |deleted| matches!(deleted, Ok(uuid) | Err((_, Some(uuid))) if *uuid == job_id),
+        );
+
+        context.tasks.spawn_run(async move {
+            if let Err(e) = delete.send(job_id).await {
                 error!("Error sending delete id {:?}", e);
             }
         });
-        while let Ok(deleted) = deleted.recv().await {
+        while let Some(deleted) = deleted.recv().await {
             match deleted {
                 Ok(uuid) => {
                     if uuid == job_id {
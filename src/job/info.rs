@@ -0,0 +1,69 @@
+#[cfg(not(feature = "has_bytes"))]
+use crate::job::job_data::job_stored_data::Job as JobKind;
+#[cfg(not(feature = "has_bytes"))]
+use crate::job::job_data::{JobStoredData, JobType};
+#[cfg(feature = "has_bytes")]
+use crate::job::job_data_prost::job_stored_data::Job as JobKind;
+#[cfg(feature = "has_bytes")]
+use crate::job::job_data_prost::{JobStoredData, JobType};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::time::Duration;
+use uuid::Uuid;
+
+///
+/// What the scheduler knows about a job, as it is in the metadata store
+#[derive(Clone, Debug)]
+pub struct JobInfo {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// The kind the code of the job is registered under in a `JobRegistry`
+    pub job_kind: Option<String>,
+    pub job_type: JobType,
+    /// The cron expression of a cron job
+    pub schedule: Option<String>,
+    /// The time zone the cron expression of a cron job is evaluated in, UTC when `None`
+    pub time_zone: Option<Tz>,
+    /// The interval of a repeated job
+    pub repeated_every: Option<Duration>,
+    pub next_tick: Option<DateTime<Utc>>,
+    pub last_tick: Option<DateTime<Utc>>,
+    /// How many times the job was started
+    pub count: u32,
+    /// Whether the job was started at least once
+    pub ran: bool,
+    /// Whether the job is paused
+    pub stopped: bool,
+}
+
+impl From<&JobStoredData> for JobInfo {
+    fn from(data: &JobStoredData) -> Self {
+        let job_type = data.job_type();
+        let schedule = match data.job.as_ref() {
+            Some(JobKind::CronJob(cj)) => Some(cj.schedule.clone()),
+            _ => None,
+        };
+        let repeated_every = match job_type {
+            JobType::Repeated => data.repeated_every_duration(),
+            _ => None,
+        };
+        Self {
+            id: data.id.as_ref().map(|id| id.into()).unwrap_or_default(),
+            name: data.name().map(|name| name.to_string()),
+            description: data.description().map(|d| d.to_string()),
+            tags: data.tags.clone(),
+            job_kind: data.job_kind().map(|kind| kind.to_string()),
+            job_type,
+            schedule,
+            time_zone: data.time_zone(),
+            repeated_every,
+            next_tick: data.next_tick_utc(),
+            last_tick: data.last_tick_utc(),
+            count: data.count,
+            ran: data.ran,
+            stopped: data.stopped,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::job::JobId;
+use crate::job_scheduler::JobsSchedulerLocked;
+use chrono::{DateTime, Utc};
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio_util::sync::CancellationToken;
+
+/// The state registered on the scheduler with `JobScheduler::set_state`, by type
+pub type SharedState = Arc<RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>;
+
+tokio::task_local! {
+    static CONTEXT: JobContext;
+}
+
+///
+/// What a run of a job gets to know about itself. Jobs created with `Job::new_with_context` and
+/// the like get it passed, other jobs can get it with `JobContext::current()`.
+#[derive(Clone)]
+pub struct JobContext {
+    pub(crate) job_id: JobId,
+    pub(crate) scheduled_at: DateTime<Utc>,
+    pub(crate) started_at: DateTime<Utc>,
+    pub(crate) attempt: u32,
+    pub(crate) run_count: u32,
+    pub(crate) manual: bool,
+    pub(crate) cancellation_token: CancellationToken,
+    #[cfg(feature = "serde")]
+    pub(crate) payload: Arc<Vec<u8>>,
+    pub(crate) state: SharedState,
+    pub(crate) scheduler: JobsSchedulerLocked,
+}
+
+impl JobContext {
+    /// The context of the run that is currently going, `None` outside of a run
+    pub fn current() -> Option<Self> {
+        CONTEXT.try_with(|context| context.clone()).ok()
+    }
+
+    pub fn job_id(&self) -> JobId {
+        self.job_id
+    }
+
+    /// When the run was due. For a retry that's when the retry was due, and for a run started
+    /// with `JobScheduler::run_now` when it was requested.
+    pub fn scheduled_at(&self) -> DateTime<Utc> {
+        self.scheduled_at
+    }
+
+    /// When the run got to start, after waiting for its turn
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    /// Which attempt this is, counting from 1. Only more than 1 when the job has a retry policy.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// How many times the job was started, this run included
+    pub fn run_count(&self) -> u32 {
+        self.run_count
+    }
+
+    /// Whether the run was requested with `JobScheduler::run_now`
+    pub fn is_manual(&self) -> bool {
+        self.manual
+    }
+
+    /// Cancelled with `JobScheduler::cancel_running`, and once the run is over, which includes it
+    /// timing out. An async job is dropped at its next await when it is cancelled, work that
+    /// can't be aborted, like a sync job, can check it to stop early.
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation_token.is_cancelled()
+    }
+
+    /// The payload the job was given with `Job::with_payload`, `None` when it has none
+    #[cfg(feature = "serde")]
+    pub fn payload<T>(&self) -> Result<Option<T>, crate::JobSchedulerError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        if self.payload.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_slice(&self.payload)
+            .map(Some)
+            .map_err(|e| crate::JobSchedulerError::Payload(e.to_string()))
+    }
+
+    /// The state of the given type that was registered with `JobScheduler::set_state`
+    pub fn state<T>(&self) -> Option<Arc<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        let r = self.state.read().unwrap_or_else(|e| e.into_inner());
+        r.get(&TypeId::of::<T>())
+            .cloned()
+            .and_then(|state| state.downcast::<T>().ok())
+    }
+
+    pub fn scheduler(&self) -> JobsSchedulerLocked {
+        self.scheduler.clone()
+    }
+
+    pub(crate) async fn scope<F: std::future::Future>(self, f: F) -> F::Output {
+        CONTEXT.scope(self, f).await
+    }
+
+    pub(crate) fn sync_scope<F: FnOnce() -> R, R>(self, f: F) -> R {
+        CONTEXT.sync_scope(self, f)
+    }
+}
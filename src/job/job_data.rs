@@ -1,18 +1,20 @@
 #[derive(Clone, PartialEq, Debug)]
 pub struct CronJob {
     pub schedule: String,
+    pub time_zone: String,
 }
 #[derive(Clone, PartialEq, Debug)]
 pub struct NonCronJob {
     pub repeating: bool,
     pub repeated_every: u64,
+    pub repeated_every_nanos: u32,
 }
 #[derive(Clone, PartialEq, Debug)]
 pub struct Uuid {
     pub id1: u64,
     pub id2: u64,
 }
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Default)]
 pub struct JobStoredData {
     pub id: ::core::option::Option<Uuid>,
     pub last_updated: ::core::option::Option<u64>,
@@ -23,6 +25,26 @@ pub struct JobStoredData {
     pub extra: Vec<u8>,
     pub ran: bool,
     pub stopped: bool,
+    pub misfire: i32,
+    pub misfire_max_runs: u32,
+    pub misfire_grace_period_ms: u64,
+    pub next_tick_nanos: u32,
+    pub last_tick_nanos: u32,
+    pub overlap_policy: i32,
+    pub timeout_ms: u64,
+    pub retry_max_attempts: u32,
+    pub retry_initial_backoff_ms: u64,
+    pub retry_multiplier: f64,
+    pub retry_max_backoff_ms: u64,
+    pub retry_jitter: f64,
+    pub retry_attempt: u32,
+    pub retry_tick: u64,
+    pub retry_tick_nanos: u32,
+    pub concurrency_group: String,
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub job_kind: String,
     pub job: ::core::option::Option<job_stored_data::Job>,
 }
 
@@ -57,6 +79,12 @@ pub struct JobAndNextTick {
     pub job_type: i32,
     pub next_tick: u64,
     pub last_tick: ::core::option::Option<u64>,
+    pub next_tick_nanos: u32,
+    pub last_tick_nanos: u32,
+    pub retry_tick: u64,
+    pub retry_tick_nanos: u32,
+    pub retry_attempt: u32,
+    pub stopped: bool,
 }
 #[derive(Clone, PartialEq, Debug)]
 pub struct ListOfUuids {
@@ -79,6 +107,15 @@ pub enum JobState {
     Started = 2,
     Done = 3,
     Removed = 4,
+    Missed = 5,
+    Skipped = 6,
+    TimedOut = 7,
+    Failed = 8,
+    Retrying = 9,
+    GaveUp = 10,
+    Panicked = 11,
+    Delayed = 12,
+    Cancelled = 13,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, FromPrimitive, ToPrimitive)]
 #[repr(i32)]
@@ -87,6 +124,23 @@ pub enum JobType {
     Repeated = 1,
     OneShot = 2,
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, FromPrimitive, ToPrimitive)]
+#[repr(i32)]
+pub enum Misfire {
+    Unset = 0,
+    FireAll = 1,
+    Skip = 2,
+    FireWithinGrace = 3,
+    FireOnce = 4,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, FromPrimitive, ToPrimitive)]
+#[repr(i32)]
+pub enum OverlapPolicy {
+    Allow = 0,
+    SkipIfRunning = 1,
+    QueueOne = 2,
+    CancelPrevious = 3,
+}
 
 impl JobState {
     pub fn from_i32(x: i32) -> Option<Self> {
@@ -96,6 +150,15 @@ impl JobState {
             2 => Some(Self::Started),
             3 => Some(Self::Done),
             4 => Some(Self::Removed),
+            5 => Some(Self::Missed),
+            6 => Some(Self::Skipped),
+            7 => Some(Self::TimedOut),
+            8 => Some(Self::Failed),
+            9 => Some(Self::Retrying),
+            10 => Some(Self::GaveUp),
+            11 => Some(Self::Panicked),
+            12 => Some(Self::Delayed),
+            13 => Some(Self::Cancelled),
             _ => None,
         }
     }
@@ -112,6 +175,31 @@ impl JobType {
     }
 }
 
+impl Misfire {
+    pub fn from_i32(x: i32) -> Option<Self> {
+        match x {
+            0 => Some(Self::Unset),
+            1 => Some(Self::FireAll),
+            2 => Some(Self::Skip),
+            3 => Some(Self::FireWithinGrace),
+            4 => Some(Self::FireOnce),
+            _ => None,
+        }
+    }
+}
+
+impl OverlapPolicy {
+    pub fn from_i32(x: i32) -> Option<Self> {
+        match x {
+            0 => Some(Self::Allow),
+            1 => Some(Self::SkipIfRunning),
+            2 => Some(Self::QueueOne),
+            3 => Some(Self::CancelPrevious),
+            _ => None,
+        }
+    }
+}
+
 impl From<JobState> for i32 {
     fn from(val: JobState) -> Self {
         val as i32
@@ -124,8 +212,28 @@ impl From<JobType> for i32 {
     }
 }
 
+impl From<Misfire> for i32 {
+    fn from(val: Misfire) -> Self {
+        val as i32
+    }
+}
+
+impl From<OverlapPolicy> for i32 {
+    fn from(val: OverlapPolicy) -> Self {
+        val as i32
+    }
+}
+
 impl JobStoredData {
     pub fn job_type(&self) -> JobType {
         JobType::from_i32(self.job_type).unwrap()
     }
+
+    pub fn overlap_policy(&self) -> OverlapPolicy {
+        OverlapPolicy::from_i32(self.overlap_policy).unwrap_or(OverlapPolicy::Allow)
+    }
+
+    pub fn set_overlap_policy(&mut self, value: OverlapPolicy) {
+        self.overlap_policy = value.into();
+    }
 }
@@ -3,6 +3,8 @@
 pub struct CronJob {
     #[prost(string, tag = "1")]
     pub schedule: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub time_zone: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -11,6 +13,8 @@ pub struct NonCronJob {
     pub repeating: bool,
     #[prost(uint64, tag = "2")]
     pub repeated_every: u64,
+    #[prost(uint32, tag = "3")]
+    pub repeated_every_nanos: u32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -41,6 +45,46 @@ pub struct JobStoredData {
     pub ran: bool,
     #[prost(bool, tag = "11")]
     pub stopped: bool,
+    #[prost(enumeration = "Misfire", tag = "12")]
+    pub misfire: i32,
+    #[prost(uint32, tag = "13")]
+    pub misfire_max_runs: u32,
+    #[prost(uint64, tag = "14")]
+    pub misfire_grace_period_ms: u64,
+    #[prost(uint32, tag = "15")]
+    pub next_tick_nanos: u32,
+    #[prost(uint32, tag = "16")]
+    pub last_tick_nanos: u32,
+    #[prost(enumeration = "OverlapPolicy", tag = "17")]
+    pub overlap_policy: i32,
+    #[prost(uint64, tag = "18")]
+    pub timeout_ms: u64,
+    #[prost(uint32, tag = "19")]
+    pub retry_max_attempts: u32,
+    #[prost(uint64, tag = "20")]
+    pub retry_initial_backoff_ms: u64,
+    #[prost(double, tag = "21")]
+    pub retry_multiplier: f64,
+    #[prost(uint64, tag = "22")]
+    pub retry_max_backoff_ms: u64,
+    #[prost(double, tag = "23")]
+    pub retry_jitter: f64,
+    #[prost(uint32, tag = "24")]
+    pub retry_attempt: u32,
+    #[prost(uint64, tag = "25")]
+    pub retry_tick: u64,
+    #[prost(uint32, tag = "26")]
+    pub retry_tick_nanos: u32,
+    #[prost(string, tag = "27")]
+    pub concurrency_group: ::prost::alloc::string::String,
+    #[prost(string, tag = "28")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(string, tag = "29")]
+    pub description: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag = "30")]
+    pub tags: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, tag = "31")]
+    pub job_kind: ::prost::alloc::string::String,
     #[prost(oneof = "job_stored_data::Job", tags = "6, 7")]
     pub job: ::core::option::Option<job_stored_data::Job>,
 }
@@ -92,6 +136,18 @@ pub struct JobAndNextTick {
     pub next_tick: u64,
     #[prost(uint64, optional, tag = "4")]
     pub last_tick: ::core::option::Option<u64>,
+    #[prost(uint32, tag = "5")]
+    pub next_tick_nanos: u32,
+    #[prost(uint32, tag = "6")]
+    pub last_tick_nanos: u32,
+    #[prost(uint64, tag = "7")]
+    pub retry_tick: u64,
+    #[prost(uint32, tag = "8")]
+    pub retry_tick_nanos: u32,
+    #[prost(uint32, tag = "9")]
+    pub retry_attempt: u32,
+    #[prost(bool, tag = "10")]
+    pub stopped: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -121,6 +177,15 @@ pub enum JobState {
     Started = 2,
     Done = 3,
     Removed = 4,
+    Missed = 5,
+    Skipped = 6,
+    TimedOut = 7,
+    Failed = 8,
+    Retrying = 9,
+    GaveUp = 10,
+    Panicked = 11,
+    Delayed = 12,
+    Cancelled = 13,
 }
 impl JobState {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -134,6 +199,15 @@ impl JobState {
             JobState::Started => "Started",
             JobState::Done => "Done",
             JobState::Removed => "Removed",
+            JobState::Missed => "Missed",
+            JobState::Skipped => "Skipped",
+            JobState::TimedOut => "TimedOut",
+            JobState::Failed => "Failed",
+            JobState::Retrying => "Retrying",
+            JobState::GaveUp => "GaveUp",
+            JobState::Panicked => "Panicked",
+            JobState::Delayed => "Delayed",
+            JobState::Cancelled => "Cancelled",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -144,6 +218,15 @@ impl JobState {
             "Started" => Some(Self::Started),
             "Done" => Some(Self::Done),
             "Removed" => Some(Self::Removed),
+            "Missed" => Some(Self::Missed),
+            "Skipped" => Some(Self::Skipped),
+            "TimedOut" => Some(Self::TimedOut),
+            "Failed" => Some(Self::Failed),
+            "Retrying" => Some(Self::Retrying),
+            "GaveUp" => Some(Self::GaveUp),
+            "Panicked" => Some(Self::Panicked),
+            "Delayed" => Some(Self::Delayed),
+            "Cancelled" => Some(Self::Cancelled),
             _ => None,
         }
     }
@@ -177,3 +260,70 @@ impl JobType {
         }
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Misfire {
+    Unset = 0,
+    FireAll = 1,
+    Skip = 2,
+    FireWithinGrace = 3,
+    FireOnce = 4,
+}
+impl Misfire {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Misfire::Unset => "Unset",
+            Misfire::FireAll => "FireAll",
+            Misfire::Skip => "Skip",
+            Misfire::FireWithinGrace => "FireWithinGrace",
+            Misfire::FireOnce => "FireOnce",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Unset" => Some(Self::Unset),
+            "FireAll" => Some(Self::FireAll),
+            "Skip" => Some(Self::Skip),
+            "FireWithinGrace" => Some(Self::FireWithinGrace),
+            "FireOnce" => Some(Self::FireOnce),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum OverlapPolicy {
+    Allow = 0,
+    SkipIfRunning = 1,
+    QueueOne = 2,
+    CancelPrevious = 3,
+}
+impl OverlapPolicy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            OverlapPolicy::Allow => "Allow",
+            OverlapPolicy::SkipIfRunning => "SkipIfRunning",
+            OverlapPolicy::QueueOne => "QueueOne",
+            OverlapPolicy::CancelPrevious => "CancelPrevious",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "Allow" => Some(Self::Allow),
+            "SkipIfRunning" => Some(Self::SkipIfRunning),
+            "QueueOne" => Some(Self::QueueOne),
+            "CancelPrevious" => Some(Self::CancelPrevious),
+            _ => None,
+        }
+    }
+}
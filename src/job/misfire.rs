@@ -0,0 +1,399 @@
+#[cfg(not(feature = "has_bytes"))]
+use crate::job::job_data::{JobType, Misfire};
+#[cfg(feature = "has_bytes")]
+use crate::job::job_data_prost::{JobType, Misfire};
+use crate::job::time_zone::next_tick_after;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use std::time::Duration;
+
+/// A tick that is later than this is considered to be missed
+pub const MISFIRE_THRESHOLD: Duration = Duration::from_secs(1);
+
+///
+/// What to do with ticks of a job that were missed, e.g. because the scheduler was down or lagging
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MisfirePolicy {
+    /// Run once for all of the missed ticks
+    #[default]
+    FireOnce,
+    /// Run once for every missed tick, but at most `max_runs` times
+    FireAll { max_runs: u32 },
+    /// Don't run for the missed ticks, wait for the next one
+    Skip,
+    /// Run once if a missed tick is within the grace period, otherwise skip to the next one
+    FireWithin(Duration),
+}
+
+impl MisfirePolicy {
+    /// The stored policy, `None` when the job didn't set one
+    pub fn from_stored(misfire: i32, max_runs: u32, grace_period_ms: u64) -> Option<Self> {
+        match Misfire::from_i32(misfire) {
+            Some(Misfire::FireOnce) => Some(MisfirePolicy::FireOnce),
+            Some(Misfire::FireAll) => Some(MisfirePolicy::FireAll { max_runs }),
+            Some(Misfire::Skip) => Some(MisfirePolicy::Skip),
+            Some(Misfire::FireWithinGrace) => Some(MisfirePolicy::FireWithin(
+                Duration::from_millis(grace_period_ms),
+            )),
+            Some(Misfire::Unset) | None => None,
+        }
+    }
+
+    /// The stored kind, max runs and grace period in milliseconds
+    pub fn to_stored(policy: Option<&Self>) -> (i32, u32, u64) {
+        match policy {
+            Some(MisfirePolicy::FireOnce) => (Misfire::FireOnce.into(), 0, 0),
+            Some(MisfirePolicy::FireAll { max_runs }) => (Misfire::FireAll.into(), *max_runs, 0),
+            Some(MisfirePolicy::Skip) => (Misfire::Skip.into(), 0, 0),
+            Some(MisfirePolicy::FireWithin(grace)) => {
+                (Misfire::FireWithinGrace.into(), 0, grace.as_millis() as u64)
+            }
+            None => (Misfire::Unset.into(), 0, 0),
+        }
+    }
+}
+
+///
+/// The ticks of a job, used to work out what was missed
+pub struct JobTicks<'a> {
+    pub job_type: JobType,
+    pub schedule: Option<&'a Schedule>,
+    pub time_zone: Option<&'a Tz>,
+    pub repeated_every: Option<Duration>,
+    /// The tick that is due
+    pub next_tick: DateTime<Utc>,
+}
+
+///
+/// What needs to happen to a job that has a tick that is due
+#[derive(Debug, PartialEq, Eq)]
+pub struct DueTick {
+    /// How many times the job needs to run
+    pub runs: u32,
+    /// Whether ticks were skipped, or collapsed into fewer runs, because of the misfire policy
+    pub missed: bool,
+    pub next_tick: Option<DateTime<Utc>>,
+}
+
+impl<'a> JobTicks<'a> {
+    /// The first tick that comes strictly after `after`
+    fn tick_after(&self, after: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.job_type {
+            JobType::Cron => self
+                .schedule
+                .and_then(|s| next_tick_after(s, self.time_zone, after)),
+            JobType::OneShot => None,
+            JobType::Repeated => {
+                let every = chrono::Duration::from_std(self.repeated_every?)
+                    .ok()?
+                    .num_nanoseconds()?;
+                if every == 0 {
+                    return Some(*after);
+                }
+                if self.next_tick > *after {
+                    return Some(self.next_tick);
+                }
+                let elapsed = (*after - self.next_tick).num_nanoseconds()? / every;
+                let to_next = (elapsed + 1).checked_mul(every)?;
+                self.next_tick
+                    .checked_add_signed(chrono::Duration::nanoseconds(to_next))
+            }
+        }
+    }
+
+    ///
+    /// The tick a paused job continues with when it is resumed. Ticks missed while it was paused
+    /// are skipped, a one shot job that became due in the meantime runs right away.
+    pub fn resumed_tick(&self, now: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.job_type {
+            JobType::OneShot => Some(self.next_tick.max(*now)),
+            _ if self.next_tick > *now => Some(self.next_tick),
+            _ => self.tick_after(now),
+        }
+    }
+
+    /// Whether there is a tick between `from` and `now`
+    fn has_tick_since(&self, from: &DateTime<Utc>, now: &DateTime<Utc>) -> bool {
+        if self.next_tick >= *from {
+            return true;
+        }
+        let from = *from - chrono::Duration::nanoseconds(1);
+        matches!(self.tick_after(&from), Some(t) if t <= *now)
+    }
+
+    /// The amount of ticks that are due, counting up to `limit`
+    fn due_ticks(&self, now: &DateTime<Utc>, limit: u32) -> u32 {
+        let mut count = 1;
+        let mut tick = self.next_tick;
+        while count < limit {
+            match self.tick_after(&tick) {
+                Some(t) if t <= *now => {
+                    count += 1;
+                    tick = t;
+                }
+                _ => break,
+            }
+        }
+        count
+    }
+
+    ///
+    /// Apply the misfire policy to a tick that is due
+    pub fn due(&self, now: &DateTime<Utc>, policy: MisfirePolicy) -> DueTick {
+        let next_tick = self.tick_after(now);
+        let threshold = chrono::Duration::from_std(MISFIRE_THRESHOLD).unwrap();
+        let misfired = *now - self.next_tick > threshold;
+        if !misfired {
+            return DueTick {
+                runs: 1,
+                missed: false,
+                next_tick,
+            };
+        }
+
+        let (runs, missed) = match policy {
+            MisfirePolicy::FireOnce => (1, self.due_ticks(now, 2) > 1),
+            MisfirePolicy::FireAll { max_runs } => {
+                let due = self.due_ticks(now, max_runs.saturating_add(1));
+                (due.min(max_runs), due > max_runs)
+            }
+            MisfirePolicy::Skip => {
+                let on_time = self.has_tick_since(&(*now - threshold), now);
+                (on_time as u32, true)
+            }
+            MisfirePolicy::FireWithin(grace) => {
+                let from = chrono::Duration::from_std(grace)
+                    .ok()
+                    .and_then(|grace| now.checked_sub_signed(grace))
+                    .unwrap_or(DateTime::<Utc>::MIN_UTC);
+                let in_grace = self.has_tick_since(&from, now);
+                (in_grace as u32, !in_grace)
+            }
+        };
+        DueTick {
+            runs,
+            missed,
+            next_tick,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+    }
+
+    fn at(millis: i64) -> DateTime<Utc> {
+        start() + chrono::Duration::milliseconds(millis)
+    }
+
+    /// Every 10 seconds, due at the start
+    fn every_ten_seconds() -> JobTicks<'static> {
+        JobTicks {
+            job_type: JobType::Repeated,
+            schedule: None,
+            time_zone: None,
+            repeated_every: Some(Duration::from_secs(10)),
+            next_tick: start(),
+        }
+    }
+
+    fn one_shot() -> JobTicks<'static> {
+        JobTicks {
+            job_type: JobType::OneShot,
+            schedule: None,
+            time_zone: None,
+            repeated_every: None,
+            next_tick: start(),
+        }
+    }
+
+    fn due_tick(runs: u32, missed: bool, next_tick: Option<DateTime<Utc>>) -> DueTick {
+        DueTick {
+            runs,
+            missed,
+            next_tick,
+        }
+    }
+
+    #[test]
+    fn a_tick_within_the_threshold_runs_whatever_the_policy() {
+        let ticks = every_ten_seconds();
+        for policy in [
+            MisfirePolicy::FireOnce,
+            MisfirePolicy::FireAll { max_runs: 5 },
+            MisfirePolicy::Skip,
+            MisfirePolicy::FireWithin(Duration::ZERO),
+        ] {
+            assert_eq!(
+                ticks.due(&at(500), policy),
+                due_tick(1, false, Some(at(10_000)))
+            );
+        }
+    }
+
+    #[test]
+    fn fire_once_runs_once_for_all_missed_ticks() {
+        // The ticks at 0, 10, 20 and 30 seconds collapse into one run
+        let ticks = every_ten_seconds();
+        assert_eq!(
+            ticks.due(&at(35_000), MisfirePolicy::FireOnce),
+            due_tick(1, true, Some(at(40_000)))
+        );
+        assert_eq!(
+            ticks.due(&at(5_000), MisfirePolicy::FireOnce),
+            due_tick(1, false, Some(at(10_000)))
+        );
+        assert_eq!(
+            one_shot().due(&at(35_000), MisfirePolicy::FireOnce),
+            due_tick(1, false, None)
+        );
+    }
+
+    #[test]
+    fn fire_all_runs_for_every_missed_tick() {
+        // The ticks at 0, 10, 20 and 30 seconds were missed
+        let ticks = every_ten_seconds();
+        assert_eq!(
+            ticks.due(&at(35_000), MisfirePolicy::FireAll { max_runs: 10 }),
+            due_tick(4, false, Some(at(40_000)))
+        );
+        assert_eq!(
+            ticks.due(&at(35_000), MisfirePolicy::FireAll { max_runs: 4 }),
+            due_tick(4, false, Some(at(40_000)))
+        );
+    }
+
+    #[test]
+    fn fire_all_is_capped_at_max_runs() {
+        let ticks = every_ten_seconds();
+        assert_eq!(
+            ticks.due(&at(35_000), MisfirePolicy::FireAll { max_runs: 2 }),
+            due_tick(2, true, Some(at(40_000)))
+        );
+        assert_eq!(
+            ticks.due(&at(35_000), MisfirePolicy::FireAll { max_runs: 3 }),
+            due_tick(3, true, Some(at(40_000)))
+        );
+    }
+
+    #[test]
+    fn skip_waits_for_the_next_tick() {
+        let ticks = every_ten_seconds();
+        assert_eq!(
+            ticks.due(&at(35_000), MisfirePolicy::Skip),
+            due_tick(0, true, Some(at(40_000)))
+        );
+        assert_eq!(
+            one_shot().due(&at(35_000), MisfirePolicy::Skip),
+            due_tick(0, true, None)
+        );
+    }
+
+    #[test]
+    fn skip_runs_a_tick_that_is_on_time() {
+        // The tick at 30 seconds is within the threshold, the ones before it were missed
+        let ticks = every_ten_seconds();
+        assert_eq!(
+            ticks.due(&at(30_500), MisfirePolicy::Skip),
+            due_tick(1, true, Some(at(40_000)))
+        );
+    }
+
+    #[test]
+    fn fire_within_runs_when_a_tick_is_within_the_grace_period() {
+        let ticks = every_ten_seconds();
+        assert_eq!(
+            ticks.due(
+                &at(35_000),
+                MisfirePolicy::FireWithin(Duration::from_secs(8))
+            ),
+            due_tick(1, false, Some(at(40_000)))
+        );
+        assert_eq!(
+            one_shot().due(
+                &at(35_000),
+                MisfirePolicy::FireWithin(Duration::from_secs(40))
+            ),
+            due_tick(1, false, None)
+        );
+    }
+
+    #[test]
+    fn fire_within_includes_a_tick_at_the_grace_boundary() {
+        // The tick at 30 seconds is exactly 5 seconds ago
+        let ticks = every_ten_seconds();
+        assert_eq!(
+            ticks.due(
+                &at(35_000),
+                MisfirePolicy::FireWithin(Duration::from_secs(5))
+            ),
+            due_tick(1, false, Some(at(40_000)))
+        );
+        assert_eq!(
+            ticks.due(
+                &at(35_000),
+                MisfirePolicy::FireWithin(Duration::from_millis(4_999))
+            ),
+            due_tick(0, true, Some(at(40_000)))
+        );
+    }
+
+    #[test]
+    fn fire_within_skips_when_the_grace_period_passed() {
+        assert_eq!(
+            one_shot().due(
+                &at(35_000),
+                MisfirePolicy::FireWithin(Duration::from_secs(5))
+            ),
+            due_tick(0, true, None)
+        );
+    }
+
+    #[test]
+    fn the_policy_survives_the_store() {
+        for policy in [
+            MisfirePolicy::FireOnce,
+            MisfirePolicy::FireAll { max_runs: 3 },
+            MisfirePolicy::Skip,
+            MisfirePolicy::FireWithin(Duration::from_millis(1_500)),
+        ] {
+            let (misfire, max_runs, grace_period_ms) = MisfirePolicy::to_stored(Some(&policy));
+            assert_eq!(
+                MisfirePolicy::from_stored(misfire, max_runs, grace_period_ms),
+                Some(policy)
+            );
+        }
+    }
+
+    #[test]
+    fn a_job_needs_fire_all_to_run_at_least_once() {
+        let mut job = crate::Job::new_repeated(Duration::from_secs(10), |_uuid, _l| {}).unwrap();
+        assert!(job
+            .set_misfire_policy(MisfirePolicy::FireAll { max_runs: 0 })
+            .is_err());
+        assert!(job
+            .set_misfire_policy(MisfirePolicy::FireAll { max_runs: 1 })
+            .is_ok());
+    }
+
+    #[test]
+    fn a_policy_that_was_not_set_is_stored_as_unset() {
+        assert_eq!(MisfirePolicy::to_stored(None), (0, 0, 0));
+        assert_eq!(MisfirePolicy::from_stored(0, 0, 0), None);
+    }
+
+    #[test]
+    fn a_resumed_job_skips_the_ticks_missed_while_paused() {
+        assert_eq!(
+            every_ten_seconds().resumed_tick(&at(35_000)),
+            Some(at(40_000))
+        );
+        assert_eq!(one_shot().resumed_tick(&at(35_000)), Some(at(35_000)));
+    }
+}
@@ -1,31 +1,40 @@
 #[cfg(not(feature = "has_bytes"))]
-use crate::job::job_data::{JobState, JobType};
+use crate::job::job_data::{JobState, JobType, OverlapPolicy};
 #[cfg(feature = "has_bytes")]
-use crate::job::job_data_prost::{JobState, JobType};
+use crate::job::job_data_prost::{JobState, JobType, OverlapPolicy};
 use crate::job_scheduler::JobsSchedulerLocked;
 use crate::{JobScheduler, JobSchedulerError, JobStoredData};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use cron::Schedule;
 use cron_job::CronJob;
+use job_context::JobContext;
+use misfire::{JobTicks, MisfirePolicy};
 use non_cron_job::NonCronJob;
+use retry::RetryPolicy;
 use std::convert::TryInto;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::{Arc, RwLock};
-use std::time::{Duration, Instant, SystemTime};
-use tokio::sync::oneshot::Receiver;
+use std::time::{Duration, Instant};
+use time_zone::next_tick_after;
 use tracing::error;
 use uuid::Uuid;
 
 mod creator;
 mod cron_job;
 mod deleter;
+pub mod info;
+pub mod job_context;
 #[cfg(not(feature = "has_bytes"))]
 pub mod job_data;
 #[cfg(feature = "has_bytes")]
 pub mod job_data_prost;
+pub mod misfire;
 mod non_cron_job;
+pub mod retry;
 mod runner;
+pub mod time_zone;
 pub mod to_code;
 use crate::notification::{NotificationCreator, NotificationDeleter};
 pub use creator::JobCreator;
@@ -43,12 +52,51 @@ pub type OnJobNotification = dyn FnMut(JobId, NotificationId, JobState) -> Pin<B
     + Send
     + Sync;
 
-fn nop(_uuid: Uuid, _jobs: JobsSchedulerLocked) {
-    // Do nothing
+pub type JobResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+pub type JobToRunFallibleAsync = dyn FnMut(JobId, JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = JobResult> + Send>>
+    + Send
+    + Sync;
+pub type JobToRunWithContext =
+    dyn FnMut(JobContext) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync;
+
+///
+/// The code a job runs
+pub enum JobRun {
+    Sync(Box<JobToRun>),
+    Async(Box<JobToRunAsync>),
+    FallibleAsync(Box<JobToRunFallibleAsync>),
+    WithContext(Box<JobToRunWithContext>),
 }
 
-fn nop_async(_uuid: Uuid, _jobs: JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = ()> + Send>> {
-    Box::pin(async move {})
+impl JobRun {
+    fn call(
+        &mut self,
+        job_id: JobId,
+        jobs: JobsSchedulerLocked,
+    ) -> Pin<Box<dyn Future<Output = JobResult> + Send>> {
+        match self {
+            JobRun::Sync(run) => {
+                (run)(job_id, jobs);
+                Box::pin(std::future::ready(Ok(())))
+            }
+            JobRun::Async(run) => {
+                let future = (run)(job_id, jobs);
+                Box::pin(async move {
+                    future.await;
+                    Ok(())
+                })
+            }
+            JobRun::FallibleAsync(run) => (run)(job_id, jobs),
+            JobRun::WithContext(run) => match JobContext::current() {
+                Some(context) => (run)(context),
+                None => {
+                    error!("No context to run job {:?} with", job_id);
+                    let e = format!("No context to run job {:?} with", job_id);
+                    Box::pin(std::future::ready(Err(e.into())))
+                }
+            },
+        }
+    }
 }
 
 ///
@@ -58,8 +106,11 @@ pub struct JobLocked(pub(crate) Arc<RwLock<Box<dyn Job + Send + Sync>>>);
 
 pub trait Job {
     fn is_cron_job(&self) -> bool;
+    /// Whether the job runs synchronous code, which is run on the blocking thread pool
+    fn is_sync(&self) -> bool;
     fn schedule(&self) -> Option<Schedule>;
-    fn repeated_every(&self) -> Option<u64>;
+    fn time_zone(&self) -> Option<Tz>;
+    fn repeated_every(&self) -> Option<Duration>;
     fn last_tick(&self) -> Option<DateTime<Utc>>;
     fn set_last_tick(&mut self, tick: Option<DateTime<Utc>>);
     fn next_tick(&self) -> Option<DateTime<Utc>>;
@@ -68,34 +119,31 @@ pub trait Job {
     fn count(&self) -> u32;
     fn increment_count(&mut self);
     fn job_id(&self) -> Uuid;
+    fn set_job_id(&mut self, id: Uuid);
     fn job_type(&self) -> JobType;
     fn ran(&self) -> bool;
     fn set_ran(&mut self, ran: bool);
     fn stop(&self) -> bool;
     fn set_stopped(&mut self);
     fn set_started(&mut self);
+    /// Whether notifications were added for the job, they're tied to its id
+    fn has_notifications(&self) -> bool;
+    fn set_has_notifications(&mut self);
     fn job_data_from_job(&mut self) -> Result<Option<JobStoredData>, JobSchedulerError>;
     fn set_job_data(&mut self, job_data: JobStoredData) -> Result<(), JobSchedulerError>;
-    fn run(&mut self, jobs: JobScheduler) -> Receiver<bool>;
+    fn run(&mut self, jobs: JobScheduler) -> Pin<Box<dyn Future<Output = JobResult> + Send>>;
 }
 
+/// The namespace of the ids made by `JobLocked::id_from_name`
+const JOB_ID_NAMESPACE: Uuid = Uuid::from_u128(0x5e1d_8b7c_2f0a_4c3e_9d61_7a84_b2c5_f013);
+
 impl JobLocked {
-    /// Create a new cron job.
-    ///
-    /// ```rust,ignore
-    /// let mut sched = JobScheduler::new();
-    /// // Run at second 0 of the 15th minute of the 6th, 8th, and 10th hour
-    /// // of any day in March and June that is a Friday of the year 2017.
-    /// let job = Job::new("0 15 6,8,10 * Mar,Jun Fri 2017", |_uuid, _lock| {
-    ///             println!("{:?} Hi I ran", chrono::Utc::now());
-    ///         });
-    /// sched.add(job)
-    /// tokio::spawn(sched.start());
-    /// ```
-    pub fn new<S, T, E>(schedule: S, run: T) -> Result<Self, JobSchedulerError>
+    fn make_cron_job<S, E>(
+        schedule: S,
+        time_zone: Option<Tz>,
+        run: JobRun,
+    ) -> Result<Self, JobSchedulerError>
     where
-        T: 'static,
-        T: FnMut(Uuid, JobsSchedulerLocked) + Send + Sync,
         S: TryInto<Schedule, Error = E>,
         E: std::error::Error + 'static,
     {
@@ -103,38 +151,58 @@ impl JobLocked {
             .try_into()
             .map_err(|_| JobSchedulerError::ParseSchedule)?;
         let job_id = Uuid::new_v4();
+        let time_zone_name = time_zone
+            .as_ref()
+            .map(|tz| tz.name().to_string())
+            .unwrap_or_default();
+        let next_tick = next_tick_after(&schedule, time_zone.as_ref(), &Utc::now());
         Ok(Self(Arc::new(RwLock::new(Box::new(CronJob {
             data: JobStoredData {
                 id: Some(job_id.into()),
-                last_updated: None,
-                last_tick: None,
-                next_tick: schedule
-                    .upcoming(Utc)
-                    .next()
-                    .map(|t| t.timestamp() as u64)
-                    .unwrap_or(0),
+                next_tick: next_tick.map(|t| t.timestamp() as u64).unwrap_or(0),
+                next_tick_nanos: next_tick.map(|t| t.timestamp_subsec_nanos()).unwrap_or(0),
                 job_type: JobType::Cron.into(),
-                count: 0,
-                extra: vec![],
-                ran: false,
-                stopped: false,
                 #[cfg(feature = "has_bytes")]
                 job: Some(job_data_prost::job_stored_data::Job::CronJob(
                     job_data_prost::CronJob {
                         schedule: schedule.to_string(),
+                        time_zone: time_zone_name,
                     },
                 )),
                 #[cfg(not(feature = "has_bytes"))]
                 job: Some(job_data::job_stored_data::Job::CronJob(job_data::CronJob {
                     schedule: schedule.to_string(),
+                    time_zone: time_zone_name,
                 })),
+                ..Default::default()
             },
-            run: Box::new(run),
-            run_async: Box::new(nop_async),
-            async_job: false,
+            run,
+            has_notifications: false,
         })))))
     }
 
+    /// Create a new cron job.
+    ///
+    /// ```rust,ignore
+    /// let mut sched = JobScheduler::new();
+    /// // Run at second 0 of the 15th minute of the 6th, 8th, and 10th hour
+    /// // of any day in March and June that is a Friday of the year 2017.
+    /// let job = Job::new("0 15 6,8,10 * Mar,Jun Fri 2017", |_uuid, _lock| {
+    ///             println!("{:?} Hi I ran", chrono::Utc::now());
+    ///         });
+    /// sched.add(job)
+    /// tokio::spawn(sched.start());
+    /// ```
+    pub fn new<S, T, E>(schedule: S, run: T) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) + Send + Sync,
+        S: TryInto<Schedule, Error = E>,
+        E: std::error::Error + 'static,
+    {
+        JobLocked::make_cron_job(schedule, None, JobRun::Sync(Box::new(run)))
+    }
+
     /// Create a new async cron job.
     ///
     /// ```rust,ignore
@@ -156,40 +224,145 @@ impl JobLocked {
         S: TryInto<Schedule, Error = E>,
         E: std::error::Error + 'static,
     {
-        let schedule: Schedule = schedule
-            .try_into()
-            .map_err(|_| JobSchedulerError::ParseSchedule)?;
-        let job_id = Uuid::new_v4();
-        Ok(Self(Arc::new(RwLock::new(Box::new(CronJob {
-            data: JobStoredData {
-                id: Some(job_id.into()),
-                last_updated: None,
-                last_tick: None,
-                next_tick: schedule
-                    .upcoming(Utc)
-                    .next()
-                    .map(|t| t.timestamp() as u64)
-                    .unwrap_or(0),
-                job_type: JobType::Cron.into(),
-                count: 0,
-                extra: vec![],
-                ran: false,
-                stopped: false,
-                #[cfg(feature = "has_bytes")]
-                job: Some(job_data_prost::job_stored_data::Job::CronJob(
-                    job_data_prost::CronJob {
-                        schedule: schedule.to_string(),
-                    },
-                )),
-                #[cfg(not(feature = "has_bytes"))]
-                job: Some(job_data::job_stored_data::Job::CronJob(job_data::CronJob {
-                    schedule: schedule.to_string(),
-                })),
-            },
-            run: Box::new(nop),
-            run_async: Box::new(run),
-            async_job: true,
-        })))))
+        JobLocked::make_cron_job(schedule, None, JobRun::Async(Box::new(run)))
+    }
+
+    /// Create a new async cron job that can fail. A run that returns an error sends a
+    /// `JobNotification::Failed` notification instead of `Done`.
+    ///
+    /// ```rust,ignore
+    /// let mut sched = JobScheduler::new();
+    /// let job = Job::new_fallible_async("0 0 * * * *", |_uuid, _lock| Box::pin( async move {
+    ///             let body = reqwest::get("https://www.rust-lang.org").await?.text().await?;
+    ///             println!("{} bytes", body.len());
+    ///             Ok(())
+    ///         }));
+    /// sched.add(job)
+    /// tokio::spawn(sched.start());
+    /// ```
+    pub fn new_fallible_async<S, T, E>(schedule: S, run: T) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = JobResult> + Send>>
+            + Send
+            + Sync,
+        S: TryInto<Schedule, Error = E>,
+        E: std::error::Error + 'static,
+    {
+        JobLocked::make_cron_job(schedule, None, JobRun::FallibleAsync(Box::new(run)))
+    }
+
+    /// Create a new async cron job that gets a `JobContext` with the id, due time, attempt and
+    /// run count of the run, a cancellation token, and the state registered on the scheduler.
+    ///
+    /// ```rust,ignore
+    /// sched.set_state(pool);
+    /// let job = Job::new_with_context("0 0 * * * *", |ctx| Box::pin(async move {
+    ///             let pool = ctx.state::<PgPool>().ok_or("no pool")?;
+    ///             println!("Run {} was due at {}", ctx.run_count(), ctx.scheduled_at());
+    ///             Ok(())
+    ///         }));
+    /// ```
+    pub fn new_with_context<S, T, E>(schedule: S, run: T) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(JobContext) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync,
+        S: TryInto<Schedule, Error = E>,
+        E: std::error::Error + 'static,
+    {
+        JobLocked::make_cron_job(schedule, None, JobRun::WithContext(Box::new(run)))
+    }
+
+    /// Create a new cron job that is evaluated in the given time zone instead of UTC.
+    ///
+    /// A wall-clock time skipped by a daylight saving transition runs shifted forward by the
+    /// length of the gap, while a wall-clock time that is repeated only runs once.
+    ///
+    /// ```rust,ignore
+    /// let mut sched = JobScheduler::new();
+    /// // Run at 02:00 every day, Amsterdam time
+    /// let job = Job::new_tz("0 0 2 * * *", chrono_tz::Europe::Amsterdam, |_uuid, _lock| {
+    ///             println!("{:?} Hi I ran", chrono::Utc::now());
+    ///         });
+    /// sched.add(job)
+    /// tokio::spawn(sched.start());
+    /// ```
+    pub fn new_tz<S, T, E>(schedule: S, time_zone: Tz, run: T) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) + Send + Sync,
+        S: TryInto<Schedule, Error = E>,
+        E: std::error::Error + 'static,
+    {
+        JobLocked::make_cron_job(schedule, Some(time_zone), JobRun::Sync(Box::new(run)))
+    }
+
+    /// Create a new async cron job that is evaluated in the given time zone instead of UTC.
+    ///
+    /// ```rust,ignore
+    /// let mut sched = JobScheduler::new();
+    /// // Run at 02:00 every day, Amsterdam time
+    /// let job = Job::new_async_tz("0 0 2 * * *", chrono_tz::Europe::Amsterdam, |_uuid, _lock| Box::pin( async move {
+    ///             println!("{:?} Hi I ran", chrono::Utc::now());
+    ///         }));
+    /// sched.add(job)
+    /// tokio::spawn(sched.start());
+    /// ```
+    pub fn new_async_tz<S, T, E>(
+        schedule: S,
+        time_zone: Tz,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = ()> + Send>>
+            + Send
+            + Sync,
+        S: TryInto<Schedule, Error = E>,
+        E: std::error::Error + 'static,
+    {
+        JobLocked::make_cron_job(schedule, Some(time_zone), JobRun::Async(Box::new(run)))
+    }
+
+    /// Create a new async cron job that can fail, evaluated in the given time zone instead of UTC.
+    pub fn new_fallible_async_tz<S, T, E>(
+        schedule: S,
+        time_zone: Tz,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = JobResult> + Send>>
+            + Send
+            + Sync,
+        S: TryInto<Schedule, Error = E>,
+        E: std::error::Error + 'static,
+    {
+        JobLocked::make_cron_job(
+            schedule,
+            Some(time_zone),
+            JobRun::FallibleAsync(Box::new(run)),
+        )
+    }
+
+    /// Create a new async cron job that gets a `JobContext`, evaluated in the given time zone
+    /// instead of UTC.
+    pub fn new_with_context_tz<S, T, E>(
+        schedule: S,
+        time_zone: Tz,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(JobContext) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync,
+        S: TryInto<Schedule, Error = E>,
+        E: std::error::Error + 'static,
+    {
+        JobLocked::make_cron_job(
+            schedule,
+            Some(time_zone),
+            JobRun::WithContext(Box::new(run)),
+        )
     }
 
     /// Create a new cron job.
@@ -238,36 +411,24 @@ impl JobLocked {
         JobLocked::new_async(schedule, run)
     }
 
-    fn make_one_shot_job(
-        duration: Duration,
-        run: Box<JobToRun>,
-        run_async: Box<JobToRunAsync>,
-        async_job: bool,
-    ) -> Result<Self, JobSchedulerError> {
+    fn make_one_shot_job(duration: Duration, run: JobRun) -> Result<Self, JobSchedulerError> {
         let id = Uuid::new_v4();
+        let next_tick = chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|d| Utc::now().checked_add_signed(d));
         let job = NonCronJob {
             run,
-            run_async,
-            async_job,
             data: JobStoredData {
                 id: Some(id.into()),
-                last_updated: None,
-                last_tick: None,
-                next_tick: SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map(|p| p.as_secs())
-                    .unwrap_or(0)
-                    + duration.as_secs(),
+                next_tick: next_tick.map(|t| t.timestamp() as u64).unwrap_or(0),
+                next_tick_nanos: next_tick.map(|t| t.timestamp_subsec_nanos()).unwrap_or(0),
                 job_type: JobType::OneShot.into(),
-                count: 0,
-                extra: vec![],
-                ran: false,
-                stopped: false,
                 #[cfg(feature = "has_bytes")]
                 job: Some(job_data_prost::job_stored_data::Job::NonCronJob(
                     job_data_prost::NonCronJob {
                         repeating: false,
                         repeated_every: duration.as_secs(),
+                        repeated_every_nanos: duration.subsec_nanos(),
                     },
                 )),
                 #[cfg(not(feature = "has_bytes"))]
@@ -275,9 +436,12 @@ impl JobLocked {
                     job_data::NonCronJob {
                         repeating: false,
                         repeated_every: duration.as_secs(),
+                        repeated_every_nanos: duration.subsec_nanos(),
                     },
                 )),
+                ..Default::default()
             },
+            has_notifications: false,
         };
 
         let job: Arc<RwLock<Box<dyn Job + Send + Sync + 'static>>> =
@@ -288,7 +452,7 @@ impl JobLocked {
 
     /// Create a new one shot job.
     ///
-    /// This is checked if it is running only after 500ms in 500ms intervals.
+    /// The scheduler wakes up when the job is due.
     /// ```rust,ignore
     /// let mut sched = JobScheduler::new();
     /// let job = Job::new_one_shot(Duration::from_secs(18), |_uuid, _l| {
@@ -302,12 +466,12 @@ impl JobLocked {
         T: 'static,
         T: FnMut(Uuid, JobsSchedulerLocked) + Send + Sync,
     {
-        JobLocked::make_one_shot_job(duration, Box::new(run), Box::new(nop_async), false)
+        JobLocked::make_one_shot_job(duration, JobRun::Sync(Box::new(run)))
     }
 
     /// Create a new async one shot job.
     ///
-    /// This is checked if it is running only after 500ms in 500ms intervals.
+    /// The scheduler wakes up when the job is due.
     /// ```rust,ignore
     /// let mut sched = JobScheduler::new();
     /// let job = Job::new_one_shot(Duration::from_secs(18), |_uuid, _l| Box::pin(async move {
@@ -323,51 +487,82 @@ impl JobLocked {
             + Send
             + Sync,
     {
-        JobLocked::make_one_shot_job(duration, Box::new(nop), Box::new(run), true)
+        JobLocked::make_one_shot_job(duration, JobRun::Async(Box::new(run)))
+    }
+
+    /// Create a new async one shot job that can fail.
+    ///
+    /// ```rust,ignore
+    /// // Run after 20 seconds
+    /// let mut sched = JobScheduler::new();
+    /// let job = Job::new_one_shot_fallible_async(Duration::from_secs(20), |_uuid, _lock| Box::pin(async move {
+    ///     std::fs::remove_file("/tmp/lock")?;
+    ///     Ok(())
+    /// }));
+    /// sched.add(job)
+    /// tokio::spawn(sched.start());
+    /// ```
+    pub fn new_one_shot_fallible_async<T>(
+        duration: Duration,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = JobResult> + Send>>
+            + Send
+            + Sync,
+    {
+        JobLocked::make_one_shot_job(duration, JobRun::FallibleAsync(Box::new(run)))
+    }
+
+    /// Create a new async one shot job that gets a `JobContext`.
+    pub fn new_one_shot_with_context<T>(
+        duration: Duration,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(JobContext) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync,
+    {
+        JobLocked::make_one_shot_job(duration, JobRun::WithContext(Box::new(run)))
     }
 
     fn make_new_one_shot_at_an_instant(
         instant: Instant,
-        run: Box<JobToRun>,
-        run_async: Box<JobToRunAsync>,
-        async_job: bool,
+        run: JobRun,
     ) -> Result<Self, JobSchedulerError> {
         let id = Uuid::new_v4();
+        let duration = instant.duration_since(Instant::now());
+        let next_tick = chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|d| Utc::now().checked_add_signed(d));
 
         let job = NonCronJob {
             run,
-            run_async,
-            async_job,
             data: JobStoredData {
                 id: Some(id.into()),
-                last_updated: None,
-                last_tick: None,
-                next_tick: chrono::Utc::now()
-                    .checked_add_signed(chrono::Duration::seconds(
-                        instant.duration_since(Instant::now()).as_secs() as i64,
-                    ))
-                    .map(|t| t.timestamp() as u64)
-                    .unwrap_or(0),
+                next_tick: next_tick.map(|t| t.timestamp() as u64).unwrap_or(0),
+                next_tick_nanos: next_tick.map(|t| t.timestamp_subsec_nanos()).unwrap_or(0),
                 job_type: JobType::OneShot.into(),
-                count: 0,
-                extra: vec![],
-                ran: false,
-                stopped: false,
                 #[cfg(feature = "has_bytes")]
                 job: Some(job_data_prost::job_stored_data::Job::NonCronJob(
                     job_data_prost::NonCronJob {
                         repeating: false,
-                        repeated_every: instant.duration_since(Instant::now()).as_secs(),
+                        repeated_every: duration.as_secs(),
+                        repeated_every_nanos: duration.subsec_nanos(),
                     },
                 )),
                 #[cfg(not(feature = "has_bytes"))]
                 job: Some(job_data::job_stored_data::Job::NonCronJob(
                     job_data::NonCronJob {
                         repeating: false,
-                        repeated_every: instant.duration_since(Instant::now()).as_secs(),
+                        repeated_every: duration.as_secs(),
+                        repeated_every_nanos: duration.subsec_nanos(),
                     },
                 )),
+                ..Default::default()
             },
+            has_notifications: false,
         };
 
         let job: Arc<RwLock<Box<dyn Job + Send + Sync + 'static>>> =
@@ -394,12 +589,7 @@ impl JobLocked {
         T: 'static,
         T: FnMut(Uuid, JobsSchedulerLocked) + Send + Sync,
     {
-        JobLocked::make_new_one_shot_at_an_instant(
-            instant,
-            Box::new(run),
-            Box::new(nop_async),
-            false,
-        )
+        JobLocked::make_new_one_shot_at_an_instant(instant, JobRun::Sync(Box::new(run)))
     }
 
     /// Create a new async one shot job that runs at an instant
@@ -422,39 +612,53 @@ impl JobLocked {
             + Send
             + Sync,
     {
-        JobLocked::make_new_one_shot_at_an_instant(instant, Box::new(nop), Box::new(run), true)
+        JobLocked::make_new_one_shot_at_an_instant(instant, JobRun::Async(Box::new(run)))
     }
 
-    fn make_new_repeated(
-        duration: Duration,
-        run: Box<JobToRun>,
-        run_async: Box<JobToRunAsync>,
-        async_job: bool,
-    ) -> Result<Self, JobSchedulerError> {
+    /// Create a new async one shot job that runs at an instant and can fail.
+    pub fn new_one_shot_at_instant_fallible_async<T>(
+        instant: std::time::Instant,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = JobResult> + Send>>
+            + Send
+            + Sync,
+    {
+        JobLocked::make_new_one_shot_at_an_instant(instant, JobRun::FallibleAsync(Box::new(run)))
+    }
+
+    /// Create a new async one shot job that runs at an instant and gets a `JobContext`.
+    pub fn new_one_shot_at_instant_with_context<T>(
+        instant: std::time::Instant,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(JobContext) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync,
+    {
+        JobLocked::make_new_one_shot_at_an_instant(instant, JobRun::WithContext(Box::new(run)))
+    }
+
+    fn make_new_repeated(duration: Duration, run: JobRun) -> Result<Self, JobSchedulerError> {
         let id = Uuid::new_v4();
+        let next_tick = chrono::Duration::from_std(duration)
+            .ok()
+            .and_then(|d| Utc::now().checked_add_signed(d));
         let job = NonCronJob {
             run,
-            run_async,
-            async_job,
             data: JobStoredData {
                 id: Some(id.into()),
-                last_updated: None,
-                last_tick: None,
-                next_tick: chrono::Utc::now()
-                    .checked_add_signed(chrono::Duration::seconds(duration.as_secs() as i64))
-                    .map(|t| t.timestamp() as u64)
-                    .unwrap_or(0),
+                next_tick: next_tick.map(|t| t.timestamp() as u64).unwrap_or(0),
+                next_tick_nanos: next_tick.map(|t| t.timestamp_subsec_nanos()).unwrap_or(0),
                 job_type: JobType::Repeated.into(),
-                count: 0,
-                extra: vec![],
-                ran: false,
-                stopped: false,
-
                 #[cfg(feature = "has_bytes")]
                 job: Some(job_data_prost::job_stored_data::Job::NonCronJob(
                     job_data_prost::NonCronJob {
                         repeating: true,
                         repeated_every: duration.as_secs(),
+                        repeated_every_nanos: duration.subsec_nanos(),
                     },
                 )),
                 #[cfg(not(feature = "has_bytes"))]
@@ -462,9 +666,12 @@ impl JobLocked {
                     job_data::NonCronJob {
                         repeating: true,
                         repeated_every: duration.as_secs(),
+                        repeated_every_nanos: duration.subsec_nanos(),
                     },
                 )),
+                ..Default::default()
             },
+            has_notifications: false,
         };
 
         let job: Arc<RwLock<Box<dyn Job + Send + Sync + 'static>>> =
@@ -475,7 +682,7 @@ impl JobLocked {
 
     /// Create a new repeated job.
     ///
-    /// This is checked if it is running only after 500ms in 500ms intervals.
+    /// The scheduler wakes up when the job is due.
     /// ```rust,ignore
     /// let mut sched = JobScheduler::new();
     /// let job = Job::new_repeated(Duration::from_secs(8), |_uuid, _lock| {
@@ -489,12 +696,12 @@ impl JobLocked {
         T: 'static,
         T: FnMut(Uuid, JobsSchedulerLocked) + Send + Sync,
     {
-        JobLocked::make_new_repeated(duration, Box::new(run), Box::new(nop_async), false)
+        JobLocked::make_new_repeated(duration, JobRun::Sync(Box::new(run)))
     }
 
     /// Create a new async repeated job.
     ///
-    /// This is checked if it is running only after 500ms in 500ms intervals.
+    /// The scheduler wakes up when the job is due.
     /// ```rust,ignore
     /// let mut sched = JobScheduler::new();
     /// let job = Job::new_repeated(Duration::from_secs(8), |_uuid, _lock| Box::pin(async move {
@@ -510,7 +717,45 @@ impl JobLocked {
             + Send
             + Sync,
     {
-        JobLocked::make_new_repeated(duration, Box::new(nop), Box::new(run), true)
+        JobLocked::make_new_repeated(duration, JobRun::Async(Box::new(run)))
+    }
+
+    /// Create a new async repeated job that can fail. A failed run doesn't stop the job from
+    /// repeating.
+    ///
+    /// ```rust,ignore
+    /// let mut sched = JobScheduler::new();
+    /// let job = Job::new_repeated_fallible_async(Duration::from_secs(8), |_uuid, _lock| Box::pin(async move {
+    ///     let size = std::fs::metadata("/var/log/syslog")?.len();
+    ///     println!("syslog is {} bytes", size);
+    ///     Ok(())
+    /// }));
+    /// sched.add(job)
+    /// tokio::spawn(sched.start());
+    /// ```
+    pub fn new_repeated_fallible_async<T>(
+        duration: Duration,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = JobResult> + Send>>
+            + Send
+            + Sync,
+    {
+        JobLocked::make_new_repeated(duration, JobRun::FallibleAsync(Box::new(run)))
+    }
+
+    /// Create a new async repeated job that gets a `JobContext`.
+    pub fn new_repeated_with_context<T>(
+        duration: Duration,
+        run: T,
+    ) -> Result<Self, JobSchedulerError>
+    where
+        T: 'static,
+        T: FnMut(JobContext) -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync,
+    {
+        JobLocked::make_new_repeated(duration, JobRun::WithContext(Box::new(run)))
     }
 
     ///
@@ -518,13 +763,14 @@ impl JobLocked {
     /// This method will also change the last tick on itself
     pub fn tick(&mut self) -> Result<bool, JobSchedulerError> {
         let now = Utc::now();
-        let (job_type, last_tick, next_tick, schedule, repeated_every, ran, count) = {
+        let (job_type, last_tick, next_tick, schedule, time_zone, repeated_every, ran, count) = {
             let r = self.0.read().map_err(|_| JobSchedulerError::TickError)?;
             (
                 r.job_type(),
                 r.last_tick(),
                 r.next_tick(),
                 r.schedule(),
+                r.time_zone(),
                 r.repeated_every(),
                 r.ran(),
                 r.count(),
@@ -559,41 +805,37 @@ impl JobLocked {
             _ => false,
         };
 
-        let next_tick = if must_run {
-            match job_type {
-                JobType::Cron => schedule.and_then(|s| s.after(&now).next()),
-                JobType::OneShot => None,
-                JobType::Repeated => repeated_every.and_then(|r| {
-                    next_tick
-                        .and_then(|nt| nt.checked_add_signed(chrono::Duration::seconds(r as i64)))
-                }),
+        let misfire_policy = match self.job_data() {
+            Ok(job_data) => job_data.misfire_policy().unwrap_or_default(),
+            Err(e) => {
+                error!("Could not get job data");
+                return Err(e);
             }
-        } else {
-            next_tick
         };
-        let last_tick = Some(now);
 
-        let job_data = self.job_data();
-        if let Err(e) = job_data {
-            error!("Could not get job data");
-            return Err(e);
-        }
+        let (runs, next_tick) = match next_tick {
+            Some(next_tick) if must_run => {
+                let due = JobTicks {
+                    job_type,
+                    schedule: schedule.as_ref(),
+                    time_zone: time_zone.as_ref(),
+                    repeated_every,
+                    next_tick,
+                }
+                .due(&now, misfire_policy);
+                (due.runs, due.next_tick)
+            }
+            next_tick => (0, next_tick),
+        };
+        let must_run = runs > 0;
+        let last_tick = Some(now);
 
         {
             let mut w = self.0.write().map_err(|_| JobSchedulerError::JobTick)?;
             w.set_next_tick(next_tick);
             w.set_last_tick(last_tick);
             w.set_ran(ran || must_run);
-            let count = if must_run {
-                if count == u32::MAX {
-                    0
-                } else {
-                    count + 1
-                }
-            } else {
-                count
-            };
-            w.set_count(count);
+            w.set_count(count.wrapping_add(runs));
         }
 
         Ok(must_run)
@@ -619,7 +861,11 @@ impl JobLocked {
             let mut job_scheduler = job_scheduler.clone();
             job_scheduler.init().await?;
         }
-        let job_id = self.guid();
+        let job_id = {
+            let mut w = self.0.write().map_err(|_| JobSchedulerError::GetJobData)?;
+            w.set_has_notifications();
+            w.job_id()
+        };
         let context = job_scheduler.context();
         NotificationCreator::add(&context, run, states, &job_id).await
     }
@@ -649,7 +895,7 @@ impl JobLocked {
             job_scheduler.init().await?;
         }
         let context = job_scheduler.context();
-        NotificationDeleter::remove(&context, notification_id, states)
+        NotificationDeleter::remove(&context, notification_id, states).await
     }
 
     ///
@@ -694,6 +940,32 @@ impl JobLocked {
             .map(|(_, deleted)| deleted)
     }
 
+    ///
+    /// Run something when a run of the task returned an error. The error message can be read with
+    /// `JobNotificationDetails::current()` inside the notification. Returns a UUID as handle for
+    /// this notification, to be used with `on_failed_notification_remove`.
+    pub async fn on_failed_notification_add(
+        &mut self,
+        job_scheduler: &JobsSchedulerLocked,
+        on_failed: Box<OnJobNotification>,
+    ) -> Result<Uuid, JobSchedulerError> {
+        self.on_notifications_add(job_scheduler, on_failed, vec![JobState::Failed])
+            .await
+    }
+
+    ///
+    /// Remove the notification when a run of the task failed. Uses the same UUID that was returned by
+    /// `on_failed_notification_add`
+    pub async fn on_failed_notification_remove(
+        &mut self,
+        job_scheduler: &JobsSchedulerLocked,
+        notification_id: &Uuid,
+    ) -> Result<bool, JobSchedulerError> {
+        self.on_notification_removal(job_scheduler, notification_id, Some(vec![JobState::Failed]))
+            .await
+            .map(|(_, deleted)| deleted)
+    }
+
     ///
     /// Run something when the task was removed. Returns a UUID as handle for this notification. This
     /// UUID needs to be used when you want to remove the notification handle using `on_removed_notification_remove`.
@@ -773,6 +1045,244 @@ impl JobLocked {
         Ok(())
     }
 
+    ///
+    /// Works the first tick of a job that didn't tick yet out again from `now`, the time of the
+    /// scheduler's clock, as the constructors work it out from the system clock
+    pub(crate) fn set_first_tick_from(
+        &mut self,
+        now: DateTime<Utc>,
+    ) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        let Some(next_tick) = job_data.next_tick_utc() else {
+            return Ok(());
+        };
+        if job_data.last_tick.is_some() || job_data.ran {
+            return Ok(());
+        }
+        let next_tick = match job_data.job_type() {
+            JobType::Cron => match job_data.schedule() {
+                Some(schedule) => next_tick_after(&schedule, job_data.time_zone().as_ref(), &now),
+                None => Some(next_tick),
+            },
+            // The ticks are a duration away from when the job was created
+            _ => next_tick.checked_add_signed(now - Utc::now()),
+        };
+        job_data.set_next_tick(next_tick);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Set what happens with ticks that were missed, e.g. because the scheduler was down. Defaults
+    /// to the misfire policy of the scheduler the job is added to, [MisfirePolicy::FireOnce]
+    /// unless it was built with another one. `FireAll` needs to run at least once.
+    /// ```rust,ignore
+    /// let mut job = Job::new("0 * * * * *", |_uuid, _l| {
+    ///     println!("I run every minute, but never to catch up");
+    /// })?;
+    /// job.set_misfire_policy(MisfirePolicy::Skip)?;
+    /// ```
+    pub fn set_misfire_policy(&mut self, policy: MisfirePolicy) -> Result<(), JobSchedulerError> {
+        if let MisfirePolicy::FireAll { max_runs: 0 } = policy {
+            return Err(JobSchedulerError::InvalidMisfirePolicy);
+        }
+        let mut job_data = self.job_data()?;
+        job_data.set_misfire_policy(Some(policy));
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Set what happens when the job is due while a previous run is still going. Defaults to
+    /// [OverlapPolicy::Allow]. Runs that are skipped because of it send a `Skipped` notification.
+    /// ```rust,ignore
+    /// let mut job = Job::new_async("0 * * * * *", |_uuid, _l| Box::pin(async move {
+    ///     tokio::time::sleep(Duration::from_secs(180)).await;
+    /// }))?;
+    /// job.set_overlap_policy(OverlapPolicy::SkipIfRunning)?;
+    /// ```
+    pub fn set_overlap_policy(&mut self, policy: OverlapPolicy) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        job_data.set_overlap_policy(policy);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Set how long a run of the job may take. A run that takes longer is cancelled and sends a
+    /// `TimedOut` notification instead of `Done`. The job can get its timeout with
+    /// `JobScheduler::timeout_for_job` to clean up before it is cancelled.
+    /// ```rust,ignore
+    /// let mut job = Job::new_async("0 * * * * *", |uuid, mut l| Box::pin(async move {
+    ///     let timeout = l.timeout_for_job(uuid).await.ok().flatten();
+    ///     println!("I have {:?} to finish", timeout);
+    /// }))?;
+    /// job.set_timeout(Some(Duration::from_secs(30)))?;
+    /// ```
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        job_data.set_timeout(timeout);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Set how the job is retried when a run fails, panics or times out. Every failed attempt
+    /// sends a `Retrying` notification until the last one, which sends `GaveUp`. A regular tick
+    /// that comes before a retry starts over with the first attempt.
+    /// ```rust,ignore
+    /// let mut job = Job::new_fallible_async("0 * * * * *", |_uuid, _l| Box::pin(async move {
+    ///     reqwest::get("https://example.com/health").await?.error_for_status()?;
+    ///     Ok(())
+    /// }))?;
+    /// job.set_retry_policy(Some(RetryPolicy {
+    ///     max_attempts: 5,
+    ///     initial_backoff: Duration::from_millis(500),
+    ///     jitter: 0.2,
+    ///     ..Default::default()
+    /// }))?;
+    /// ```
+    pub fn set_retry_policy(
+        &mut self,
+        retry_policy: Option<RetryPolicy>,
+    ) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        job_data.set_retry_policy(retry_policy);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Put the job in a concurrency group. The scheduler can limit how many jobs of a group run at
+    /// the same time with `JobScheduler::set_concurrency_limit`.
+    /// ```rust,ignore
+    /// job.set_concurrency_group(Some("db"))?;
+    /// sched.set_concurrency_limit("db", 10).await?;
+    /// ```
+    pub fn set_concurrency_group(&mut self, group: Option<&str>) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        job_data.set_concurrency_group(group);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Give the job a name, to tell it apart in logs and when looking it up with
+    /// `JobScheduler::find_by_name`
+    pub fn set_name(&mut self, name: Option<&str>) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        job_data.set_name(name);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Describe what the job does
+    pub fn set_description(&mut self, description: Option<&str>) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        job_data.set_description(description);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Tag the job, replacing the tags it had. Jobs can be looked up and removed by tag.
+    /// ```rust,ignore
+    /// job.set_tags(&["billing", "nightly"])?;
+    /// sched.remove_by_tag("billing").await?;
+    /// ```
+    pub fn set_tags(&mut self, tags: &[&str]) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        job_data.set_tags(tags);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Set the kind of the job, under which its code is registered in a `JobRegistry`. A job
+    /// that is loaded from a persistent store after a restart runs the code of its kind.
+    /// ```rust,ignore
+    /// job.set_job_kind(Some("send-report"))?;
+    /// ```
+    pub fn set_job_kind(&mut self, kind: Option<&str>) -> Result<(), JobSchedulerError> {
+        let mut job_data = self.job_data()?;
+        job_data.set_job_kind(kind);
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Give the job arguments, which are stored with it so they're still there after a restart.
+    /// The job reads them from its context with `JobContext::payload::<T>()`. Outside of a run,
+    /// `JobScheduler::payload` looks them up by the id of the job.
+    /// ```rust,ignore
+    /// #[derive(Serialize, Deserialize)]
+    /// struct Report { customer: String }
+    ///
+    /// let job = Job::new_with_context("0 0 6 * * *", |ctx| Box::pin(async move {
+    ///     let report = ctx.payload::<Report>()?.ok_or("no report")?;
+    ///     send_report(&report.customer).await
+    /// }))?
+    /// .with_payload(&Report { customer: "X".into() })?;
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn with_payload<T>(mut self, payload: &T) -> Result<Self, JobSchedulerError>
+    where
+        T: serde::Serialize,
+    {
+        self.set_payload(payload)?;
+        Ok(self)
+    }
+
+    ///
+    /// Like `with_payload`, for a job that is already bound to a variable
+    #[cfg(feature = "serde")]
+    pub fn set_payload<T>(&mut self, payload: &T) -> Result<(), JobSchedulerError>
+    where
+        T: serde::Serialize,
+    {
+        let mut job_data = self.job_data()?;
+        job_data.set_payload(payload)?;
+        self.set_job_data(job_data)
+    }
+
+    ///
+    /// Give a job that was just created an id of its own instead of the random one it was created
+    /// with, so it can be registered again with `JobScheduler::add_or_replace` after a restart
+    /// without ending up in the store twice.
+    /// ```rust,ignore
+    /// let job = Job::new("0 0 2 * * *", |_uuid, _l| println!("Nightly report"))?
+    ///     .with_id(Job::id_from_name("nightly-report"));
+    /// sched.add_or_replace(job).await?;
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// When notifications were added for the job already, they're tied to the id it had when they
+    /// were added. `set_id` returns an error for that instead.
+    pub fn with_id(self, id: Uuid) -> Self {
+        {
+            let mut w = self.0.write().unwrap_or_else(|e| e.into_inner());
+            assert!(
+                !w.has_notifications(),
+                "The id of a job with notifications can't change"
+            );
+            w.set_job_id(id);
+        }
+        self
+    }
+
+    ///
+    /// Like `with_id`, for a job that is already bound to a variable. It fails with
+    /// `CantChangeId` once notifications were added for the job.
+    pub fn set_id(&mut self, id: Uuid) -> Result<(), JobSchedulerError> {
+        let mut w = self
+            .0
+            .write()
+            .map_err(|_| JobSchedulerError::UpdateJobData)?;
+        if w.has_notifications() {
+            return Err(JobSchedulerError::CantChangeId);
+        }
+        w.set_job_id(id);
+        Ok(())
+    }
+
+    ///
+    /// The id for a job with the given name, which is the same every time
+    pub fn id_from_name(name: &str) -> Uuid {
+        Uuid::new_v5(&JOB_ID_NAMESPACE, name.as_bytes())
+    }
+
     ///
     /// Get the job data
     pub fn job_data(&mut self) -> Result<JobStoredData, JobSchedulerError> {
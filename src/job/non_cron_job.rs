@@ -2,19 +2,20 @@
 use crate::job::job_data::{JobStoredData, JobType};
 #[cfg(feature = "has_bytes")]
 use crate::job::job_data_prost::{JobStoredData, JobType};
-use crate::job::{Job, JobToRunAsync};
-use crate::{JobScheduler, JobSchedulerError, JobToRun};
+use crate::job::{Job, JobResult, JobRun};
+use crate::{JobScheduler, JobSchedulerError};
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use cron::Schedule;
-use tokio::sync::oneshot::Receiver;
-use tracing::error;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
 use uuid::Uuid;
 
 pub struct NonCronJob {
-    pub run: Box<JobToRun>,
-    pub run_async: Box<JobToRunAsync>,
+    pub run: JobRun,
     pub data: JobStoredData,
-    pub async_job: bool,
+    pub has_notifications: bool,
 }
 
 impl Job for NonCronJob {
@@ -22,25 +23,20 @@ impl Job for NonCronJob {
         false
     }
 
+    fn is_sync(&self) -> bool {
+        matches!(self.run, JobRun::Sync(_))
+    }
+
     fn schedule(&self) -> Option<Schedule> {
         None
     }
 
-    #[cfg(feature = "has_bytes")]
-    fn repeated_every(&self) -> Option<u64> {
-        self.data.job.as_ref().and_then(|jt| match jt {
-            crate::job::job_data_prost::job_stored_data::Job::CronJob(_) => None,
-            crate::job::job_data_prost::job_stored_data::Job::NonCronJob(ncj) => {
-                Some(ncj.repeated_every)
-            }
-        })
+    fn time_zone(&self) -> Option<Tz> {
+        None
     }
-    #[cfg(not(feature = "has_bytes"))]
-    fn repeated_every(&self) -> Option<u64> {
-        self.data.job.as_ref().and_then(|jt| match jt {
-            crate::job::job_data::job_stored_data::Job::CronJob(_) => None,
-            crate::job::job_data::job_stored_data::Job::NonCronJob(ncj) => Some(ncj.repeated_every),
-        })
+
+    fn repeated_every(&self) -> Option<Duration> {
+        self.data.repeated_every_duration()
     }
 
     fn last_tick(&self) -> Option<DateTime<Utc>> {
@@ -79,6 +75,10 @@ impl Job for NonCronJob {
         self.data.id.as_ref().cloned().map(|e| e.into()).unwrap()
     }
 
+    fn set_job_id(&mut self, id: Uuid) {
+        self.data.id = Some(id.into());
+    }
+
     fn job_type(&self) -> JobType {
         self.data.job_type()
     }
@@ -103,6 +103,14 @@ impl Job for NonCronJob {
         self.data.stopped = false;
     }
 
+    fn has_notifications(&self) -> bool {
+        self.has_notifications
+    }
+
+    fn set_has_notifications(&mut self) {
+        self.has_notifications = true;
+    }
+
     fn job_data_from_job(&mut self) -> Result<Option<JobStoredData>, JobSchedulerError> {
         Ok(Some(self.data.clone()))
     }
@@ -112,24 +120,8 @@ impl Job for NonCronJob {
         Ok(())
     }
 
-    fn run(&mut self, jobs: JobScheduler) -> Receiver<bool> {
-        let (tx, rx) = tokio::sync::oneshot::channel();
+    fn run(&mut self, jobs: JobScheduler) -> Pin<Box<dyn Future<Output = JobResult> + Send>> {
         let job_id = self.job_id();
-
-        if !self.async_job {
-            (self.run)(job_id, jobs);
-            if let Err(e) = tx.send(true) {
-                error!("Error notifying done {:?}", e);
-            }
-        } else {
-            let future = (self.run_async)(job_id, jobs);
-            tokio::task::spawn(async move {
-                future.await;
-                if let Err(e) = tx.send(true) {
-                    error!("Error notifying done {:?}", e);
-                }
-            });
-        }
-        rx
+        self.run.call(job_id, jobs)
     }
 }
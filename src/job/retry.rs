@@ -0,0 +1,190 @@
+use std::time::Duration;
+
+///
+/// How a job is retried when a run fails, panics or times out. Retries are scheduled by the
+/// scheduler besides the regular ticks of the job, so a cron job keeps its schedule while it is
+/// retried.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// How many times a run is attempted in total, including the first attempt
+    pub max_attempts: u32,
+    /// How long to wait before the first retry
+    pub initial_backoff: Duration,
+    /// What the backoff is multiplied with after every retry
+    pub multiplier: f64,
+    /// The longest the backoff can become
+    pub max_backoff: Duration,
+    /// Fraction of the backoff, between 0 and 1, that is randomly taken off to spread out retries
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            multiplier: 2.0,
+            max_backoff: Duration::from_secs(60),
+            jitter: 0.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    ///
+    /// How long to wait for the retry after the given attempt failed, counting from 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let secs = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let backoff = if secs.is_finite() && secs >= 0.0 {
+            Duration::from_secs_f64(secs.min(self.max_backoff.as_secs_f64()))
+        } else {
+            self.max_backoff
+        };
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter > 0.0 {
+            backoff.mul_f64(1.0 - jitter * fastrand::f64())
+        } else {
+            backoff
+        }
+    }
+
+    pub fn from_stored(
+        max_attempts: u32,
+        initial_backoff_ms: u64,
+        multiplier: f64,
+        max_backoff_ms: u64,
+        jitter: f64,
+    ) -> Option<Self> {
+        if max_attempts == 0 {
+            return None;
+        }
+        Some(Self {
+            max_attempts,
+            initial_backoff: Duration::from_millis(initial_backoff_ms),
+            multiplier,
+            max_backoff: Duration::from_millis(max_backoff_ms),
+            jitter,
+        })
+    }
+
+    /// The stored max attempts, initial backoff in milliseconds, multiplier, max backoff in
+    /// milliseconds and jitter
+    pub fn to_stored(policy: Option<&Self>) -> (u32, u64, f64, u64, f64) {
+        match policy {
+            Some(p) => (
+                p.max_attempts.max(1),
+                p.initial_backoff.as_millis() as u64,
+                p.multiplier,
+                p.max_backoff.as_millis() as u64,
+                p.jitter,
+            ),
+            None => (0, 0, 0.0, 0, 0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(jitter: f64) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            multiplier: 2.0,
+            max_backoff: Duration::from_secs(1),
+            jitter,
+        }
+    }
+
+    #[test]
+    fn the_backoff_grows_with_every_attempt() {
+        let policy = policy(0.0);
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn the_backoff_is_capped() {
+        let policy = policy(0.0);
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(1_000), Duration::from_secs(1));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn a_multiplier_of_one_keeps_the_backoff() {
+        let policy = RetryPolicy {
+            multiplier: 1.0,
+            ..policy(0.0)
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(7), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn an_invalid_multiplier_waits_the_max_backoff() {
+        for multiplier in [f64::NAN, f64::INFINITY, -2.0] {
+            let policy = RetryPolicy {
+                multiplier,
+                ..policy(0.0)
+            };
+            assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn jitter_takes_off_at_most_its_fraction() {
+        let policy = policy(0.25);
+        for attempt in 1..=6 {
+            let full = RetryPolicy {
+                jitter: 0.0,
+                ..policy
+            }
+            .backoff(attempt);
+            for _ in 0..100 {
+                let backoff = policy.backoff(attempt);
+                assert!(backoff <= full, "{:?} > {:?}", backoff, full);
+                assert!(
+                    backoff >= full.mul_f64(0.75),
+                    "{:?} < 0.75 * {:?}",
+                    backoff,
+                    full
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn jitter_is_clamped_between_0_and_1() {
+        let policy = policy(3.0);
+        for _ in 0..100 {
+            assert!(policy.backoff(1) <= Duration::from_millis(100));
+        }
+        let policy = RetryPolicy {
+            jitter: -1.0,
+            ..policy
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn the_policy_survives_the_store() {
+        let policy = policy(0.5);
+        let (max_attempts, initial_backoff_ms, multiplier, max_backoff_ms, jitter) =
+            RetryPolicy::to_stored(Some(&policy));
+        let stored = RetryPolicy::from_stored(
+            max_attempts,
+            initial_backoff_ms,
+            multiplier,
+            max_backoff_ms,
+            jitter,
+        );
+        assert_eq!(stored, Some(policy));
+        assert_eq!(RetryPolicy::to_stored(None), (0, 0, 0.0, 0, 0.0));
+        assert_eq!(RetryPolicy::from_stored(0, 0, 0.0, 0, 0.0), None);
+    }
+}
@@ -1,62 +1,589 @@
-use crate::context::Context;
+use crate::channel::FanOut;
+use crate::clock::Clock;
+use crate::context::{BackgroundTasks, Context, JobActivation, PendingJob, PendingWork};
+use crate::job::job_context::{JobContext, SharedState};
 #[cfg(not(feature = "has_bytes"))]
-use crate::job::job_data::JobState;
+use crate::job::job_data::{JobState, JobStoredData, OverlapPolicy};
 #[cfg(feature = "has_bytes")]
-use crate::job::job_data_prost::JobState;
+use crate::job::job_data_prost::{JobState, JobStoredData, OverlapPolicy};
 use crate::job::to_code::JobCode;
 use crate::job_scheduler::JobsSchedulerLocked;
+use crate::notification::JobNotificationDetails;
+use crate::store::MetaDataStorage;
 use crate::JobSchedulerError;
+use std::any::Any;
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::broadcast::{Receiver, Sender};
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::{Notify, OwnedSemaphorePermit, RwLock, Semaphore};
+use tokio::task::AbortHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::error;
 use uuid::Uuid;
 
+///
+/// A run of a job that is still going
+struct RunningJob {
+    run_id: u64,
+    /// Cancels the run that is going
+    token: CancellationToken,
+    /// The run that starts once this one is done
+    queued: Option<(JobActivation, PendingJob)>,
+}
+
+type RunningJobs = Arc<std::sync::Mutex<HashMap<Uuid, RunningJob>>>;
+
+///
+/// Removes the entry of a job's runs once they are over, also when they are dropped by a stop
+struct RunningEntry {
+    running: RunningJobs,
+    uuid: Uuid,
+    run_id: u64,
+}
+
+impl Drop for RunningEntry {
+    fn drop(&mut self) {
+        let mut w = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if matches!(w.get(&self.uuid), Some(current) if current.run_id == self.run_id) {
+            w.remove(&self.uuid);
+        }
+    }
+}
+
+///
+/// Aborts a run when it is no longer waited for, because it timed out or its run was cancelled
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+///
+/// The cancellation tokens of the runs that are going, by job
+#[derive(Clone, Default)]
+struct InFlight {
+    next_id: Arc<AtomicU64>,
+    runs: Arc<std::sync::Mutex<HashMap<Uuid, HashMap<u64, CancellationToken>>>>,
+}
+
+impl InFlight {
+    fn track(&self, uuid: Uuid, token: CancellationToken) -> InFlightRun {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut w = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        w.entry(uuid).or_default().insert(id, token);
+        InFlightRun {
+            in_flight: self.clone(),
+            uuid,
+            id,
+        }
+    }
+
+    /// Cancels the runs of the job, returns how many there were
+    fn cancel(&self, uuid: Uuid) -> usize {
+        let r = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        let tokens = r.get(&uuid).map(|runs| runs.values()).into_iter().flatten();
+        tokens.map(|token| token.cancel()).count()
+    }
+
+    /// The jobs that have runs going
+    fn job_ids(&self) -> Vec<Uuid> {
+        let r = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        r.keys().copied().collect()
+    }
+
+    /// Cancels the runs of all jobs, returns how many there were
+    fn cancel_all(&self) -> usize {
+        let r = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        let tokens = r.values().flat_map(|runs| runs.values());
+        tokens.map(|token| token.cancel()).count()
+    }
+
+    /// Forgets the runs of all jobs
+    fn clear(&self) {
+        let mut w = self.runs.lock().unwrap_or_else(|e| e.into_inner());
+        w.clear();
+    }
+}
+
+///
+/// Stops tracking a run once it is over
+struct InFlightRun {
+    in_flight: InFlight,
+    uuid: Uuid,
+    id: u64,
+}
+
+impl Drop for InFlightRun {
+    fn drop(&mut self) {
+        let mut w = self
+            .in_flight
+            .runs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(runs) = w.get_mut(&self.uuid) {
+            runs.remove(&self.id);
+            if runs.is_empty() {
+                w.remove(&self.uuid);
+            }
+        }
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => "Job panicked".to_string(),
+        },
+    }
+}
+
+///
+/// Limits how many jobs run at the same time, in total and per concurrency group
+#[derive(Clone, Default)]
+struct ConcurrencyLimits {
+    global: Arc<RwLock<Option<Arc<Semaphore>>>>,
+    groups: Arc<RwLock<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl ConcurrencyLimits {
+    /// The semaphores a run of a job in the group needs a permit of, the group's first
+    async fn semaphores(&self, group: Option<&str>) -> Vec<Arc<Semaphore>> {
+        let mut semaphores = Vec::with_capacity(2);
+        if let Some(group) = group {
+            let groups = self.groups.read().await;
+            if let Some(semaphore) = groups.get(group) {
+                semaphores.push(semaphore.clone());
+            }
+        }
+        if let Some(semaphore) = self.global.read().await.as_ref() {
+            semaphores.push(semaphore.clone());
+        }
+        semaphores
+    }
+}
+
+///
+/// How a job is run, from its metadata
+struct RunOptions {
+    overlap_policy: OverlapPolicy,
+    timeout: Option<Duration>,
+    concurrency_group: Option<String>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            overlap_policy: OverlapPolicy::Allow,
+            timeout: None,
+            concurrency_group: None,
+        }
+    }
+}
+
+///
+/// What the runs of jobs share
+#[derive(Clone)]
+struct RunContext {
+    job_code: Arc<RwLock<Box<dyn JobCode + Send + Sync>>>,
+    storage: Arc<RwLock<Box<dyn MetaDataStorage + Send + Sync>>>,
+    jobs_changed: Arc<Notify>,
+    tx_notify: FanOut<(Uuid, JobState, JobNotificationDetails)>,
+    job_scheduler: JobsSchedulerLocked,
+    limits: ConcurrencyLimits,
+    state: SharedState,
+    in_flight: InFlight,
+    tasks: BackgroundTasks,
+    clock: Arc<dyn Clock>,
+    pending: PendingWork,
+}
+
+impl RunContext {
+    async fn notify(&self, uuid: Uuid, state: JobState, details: JobNotificationDetails) {
+        let notification = (uuid, state, details);
+        if let Err(e) = self.pending.send(uuid, &self.tx_notify, notification).await {
+            error!("Error sending {:?} notification {:?}", state, e);
+        }
+    }
+
+    /// Waits until the concurrency limits allow the job to run. A `Delayed` notification is sent
+    /// when it has to wait.
+    async fn wait_for_turn(
+        &self,
+        uuid: Uuid,
+        options: &RunOptions,
+        details: &JobNotificationDetails,
+    ) -> Vec<OwnedSemaphorePermit> {
+        let semaphores = self
+            .limits
+            .semaphores(options.concurrency_group.as_deref())
+            .await;
+        let mut permits = Vec::with_capacity(semaphores.len());
+        let mut delayed = false;
+        for semaphore in semaphores {
+            let permit = match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Ok(permit),
+                Err(_) => {
+                    if !delayed {
+                        delayed = true;
+                        self.notify(uuid, JobState::Delayed, details.clone()).await;
+                    }
+                    semaphore.acquire_owned().await
+                }
+            };
+            match permit {
+                Ok(permit) => permits.push(permit),
+                Err(e) => error!("Error waiting for concurrency limit {:?}", e),
+            }
+        }
+        permits
+    }
+
+    async fn run_once(
+        &self,
+        activation: JobActivation,
+        // Keeps the job from being removed until the run and its notifications are done
+        _pending: PendingJob,
+        token: CancellationToken,
+        options: &RunOptions,
+    ) {
+        let uuid = activation.job_id;
+        let run = JobNotificationDetails::run(activation.manual);
+        let code = {
+            let mut w = self.job_code.write().await;
+            match w.get(uuid).await {
+                Ok(Some(job)) => job,
+                _ => {
+                    error!("Error getting {:?} from job code", uuid);
+                    return;
+                }
+            }
+        };
+        let _in_flight = self.in_flight.track(uuid, token.clone());
+        // Cancels the token once the run is over, however it ends
+        let _cancel = token.clone().drop_guard();
+        // The code is fetched first, a one shot job can be removed while its run waits
+        let waiting = self.wait_for_turn(uuid, options, &run);
+        let Some(_permits) = token.run_until_cancelled(waiting).await else {
+            self.notify(uuid, JobState::Cancelled, run).await;
+            self.reset_retries(uuid).await;
+            return;
+        };
+        self.notify(uuid, JobState::Started, run.clone()).await;
+        let job_data = self.record_run(uuid).await;
+        let context = self.job_context(activation, job_data.as_ref(), token.clone());
+        let job_scheduler = self.job_scheduler.clone();
+        // The run gets its own task so a panic in it ends up in the join handle, also one of the
+        // code of the job before it returns its future
+        let handle = tokio::spawn(context.scope(async move {
+            let v = {
+                let mut job = code.write().await;
+                (job)(uuid, job_scheduler)
+            };
+            v.await
+        }));
+        let _abort = AbortOnDrop(handle.abort_handle());
+        let until_cancelled = token.run_until_cancelled(handle);
+        let result = match options.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, until_cancelled).await,
+            None => Ok(until_cancelled.await),
+        };
+        let (state, details) = match result {
+            Ok(Some(Ok(Ok(())))) => (JobState::Done, run),
+            Ok(Some(Ok(Err(e)))) => (JobState::Failed, run.failed(e.to_string())),
+            Ok(Some(Err(e))) if e.is_panic() => {
+                let message = panic_message(e.into_panic());
+                error!("Job {:?} panicked: {}", uuid, message);
+                (JobState::Panicked, run.failed(message))
+            }
+            Ok(Some(Err(e))) => (JobState::Failed, run.failed(e.to_string())),
+            Ok(None) => (JobState::Cancelled, run),
+            Err(_) => (JobState::TimedOut, run),
+        };
+        self.notify(uuid, state, details.clone()).await;
+
+        match state {
+            // A cancelled run isn't retried, the next run starts over with the first attempt
+            JobState::Done | JobState::Cancelled => self.reset_retries(uuid).await,
+            _ => {
+                if let Some((state, attempt)) = self.retry_after_failure(uuid).await {
+                    let details = JobNotificationDetails {
+                        attempt: Some(attempt),
+                        ..details
+                    };
+                    self.notify(uuid, state, details).await;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Schedules the next attempt of a failed run, or gives up when it was the last one. Returns
+    /// the state to notify and the attempt that failed, `None` when the job isn't retried.
+    async fn retry_after_failure(&self, uuid: Uuid) -> Option<(JobState, u32)> {
+        let mut w = self.storage.write().await;
+        let job = match w.get(uuid).await {
+            Ok(Some(job)) => job,
+            Ok(None) => return None,
+            Err(e) => {
+                error!("Error getting job metadata {:?}", e);
+                return None;
+            }
+        };
+        let policy = job.retry_policy()?;
+        let attempt = job.retry_attempt.max(1);
+        let (retry_tick, next_attempt, state) = if attempt < policy.max_attempts {
+            let backoff = chrono::Duration::from_std(policy.backoff(attempt))
+                .unwrap_or_else(|_| chrono::Duration::zero());
+            (
+                Some(self.clock.now() + backoff),
+                attempt + 1,
+                JobState::Retrying,
+            )
+        } else {
+            (None, 0, JobState::GaveUp)
+        };
+        if let Err(e) = w.set_retry(uuid, retry_tick, next_attempt).await {
+            error!("Error scheduling retry {:?}", e);
+            return None;
+        }
+        if retry_tick.is_some() {
+            self.jobs_changed.notify_one();
+        }
+        Some((state, attempt))
+    }
+
+    /// Counts a run of the job that started, returns the job's metadata after counting it
+    async fn record_run(&self, uuid: Uuid) -> Option<JobStoredData> {
+        let mut w = self.storage.write().await;
+        match w.get(uuid).await {
+            Ok(Some(mut job)) => {
+                job.count = job.count.saturating_add(1);
+                job.ran = true;
+                if let Err(e) = w.add_or_update(job.clone()).await {
+                    error!("Error recording run {:?}", e);
+                }
+                Some(job)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                error!("Error getting job metadata {:?}", e);
+                None
+            }
+        }
+    }
+
+    fn job_context(
+        &self,
+        activation: JobActivation,
+        job: Option<&JobStoredData>,
+        cancellation_token: CancellationToken,
+    ) -> JobContext {
+        JobContext {
+            job_id: activation.job_id,
+            scheduled_at: activation.scheduled_at,
+            started_at: self.clock.now(),
+            attempt: job.map(|job| job.retry_attempt.max(1)).unwrap_or(1),
+            run_count: job.map(|job| job.count).unwrap_or_default(),
+            manual: activation.manual,
+            cancellation_token,
+            #[cfg(feature = "serde")]
+            payload: Arc::new(job.map(|job| job.extra.clone()).unwrap_or_default()),
+            state: self.state.clone(),
+            scheduler: self.job_scheduler.clone(),
+        }
+    }
+
+    /// Clears the attempts of a job after a run succeeded
+    async fn reset_retries(&self, uuid: Uuid) {
+        let mut w = self.storage.write().await;
+        match w.get(uuid).await {
+            Ok(Some(job)) if job.retry_attempt != 0 || job.retry_tick != 0 => {
+                if let Err(e) = w.set_retry(uuid, None, 0).await {
+                    error!("Error resetting retries {:?}", e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => error!("Error getting job metadata {:?}", e),
+        }
+    }
+
+    async fn run_options(&self, uuid: Uuid) -> RunOptions {
+        let mut w = self.storage.write().await;
+        match w.get(uuid).await {
+            Ok(Some(job)) => RunOptions {
+                overlap_policy: job.overlap_policy(),
+                timeout: job.timeout(),
+                concurrency_group: job.concurrency_group().map(|g| g.to_string()),
+            },
+            Ok(None) => RunOptions::default(),
+            Err(e) => {
+                error!("Error getting job metadata {:?}", e);
+                RunOptions::default()
+            }
+        }
+    }
+}
+
 #[derive(Default)]
-pub struct JobRunner {}
+pub struct JobRunner {
+    running: RunningJobs,
+    limits: ConcurrencyLimits,
+    in_flight: InFlight,
+}
 
 impl JobRunner {
     async fn listen_for_activations(
-        job_code: Arc<RwLock<Box<dyn JobCode + Send + Sync>>>,
-        mut rx: Receiver<Uuid>,
-        tx_notify: Sender<(Uuid, JobState)>,
-        job_scheduler: JobsSchedulerLocked,
+        runs: RunContext,
+        running: RunningJobs,
+        mut rx: Receiver<JobActivation>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving {:?}", e);
-                break;
-            }
-            let uuid = val.unwrap();
-            {
-                let tx = tx_notify.clone();
-                tokio::spawn(async move {
-                    if let Err(e) = tx.send((uuid, JobState::Started)) {
-                        error!("Error sending error listening for activation {:?}", e);
-                    }
+        let mut next_run_id: u64 = 0;
+        while let Some(activation) = rx.recv().await {
+            let uuid = activation.job_id;
+            let _busy = runs.tasks.busy();
+            let pending = runs.pending.take(uuid);
+
+            let options = runs.run_options(uuid).await;
+            if matches!(options.overlap_policy, OverlapPolicy::Allow) {
+                let tasks = runs.tasks.clone();
+                let runs = runs.clone();
+                tasks.spawn_run(async move {
+                    let token = CancellationToken::new();
+                    runs.run_once(activation, pending, token, &options).await;
                 });
+                continue;
             }
-            let mut w = job_code.write().await;
-            let code = w.get(uuid).await;
-            match code {
-                Ok(Some(job)) => {
-                    let mut job = job.write().await;
-                    let v = (job)(uuid, job_scheduler.clone());
-                    let tx = tx_notify.clone();
-                    tokio::spawn(async move {
-                        v.await;
-                        if let Err(e) = tx.send((uuid, JobState::Done)) {
-                            error!("Error sending spawned task {:?}", e);
-                        }
-                    });
+
+            next_run_id = next_run_id.wrapping_add(1);
+            let run_id = next_run_id;
+            let token = CancellationToken::new();
+            // The entry is added before the run is spawned, so the run can't finish before it
+            let skipped = {
+                let mut w = running.lock().unwrap_or_else(|e| e.into_inner());
+                let skipped = match (w.get_mut(&uuid), &options.overlap_policy) {
+                    (None, _) => false,
+                    (Some(previous), OverlapPolicy::QueueOne) if previous.queued.is_none() => {
+                        previous.queued = Some((activation, pending));
+                        continue;
+                    }
+                    (Some(previous), OverlapPolicy::CancelPrevious) => {
+                        // The previous run sends `Cancelled` once it sees its token
+                        previous.token.cancel();
+                        false
+                    }
+                    (Some(_), _) => true,
+                };
+                if !skipped {
+                    let running_job = RunningJob {
+                        run_id,
+                        token: token.clone(),
+                        queued: None,
+                    };
+                    w.insert(uuid, running_job);
                 }
-                _ => {
-                    error!("Error getting {:?} from job code", uuid);
-                    continue;
+                skipped
+            };
+            if skipped {
+                let details = JobNotificationDetails::run(activation.manual);
+                runs.notify(uuid, JobState::Skipped, details).await;
+                continue;
+            }
+
+            let runs = runs.clone();
+            let entry = RunningEntry {
+                running: running.clone(),
+                uuid,
+                run_id,
+            };
+            runs.tasks.clone().spawn_run(async move {
+                let mut next = (activation, pending, token);
+                loop {
+                    let (activation, pending, token) = next;
+                    runs.run_once(activation, pending, token, &options).await;
+                    let mut w = entry.running.lock().unwrap_or_else(|e| e.into_inner());
+                    match w.get_mut(&uuid) {
+                        Some(current) if current.run_id == run_id => match current.queued.take() {
+                            Some((activation, pending)) => {
+                                current.token = CancellationToken::new();
+                                next = (activation, pending, current.token.clone());
+                            }
+                            // The entry is removed once it is dropped
+                            None => break,
+                        },
+                        _ => break,
+                    }
                 }
+            });
+        }
+    }
+
+    ///
+    /// Cancels the runs of the job that are going, and a run that is queued after them. Returns
+    /// how many runs were cancelled.
+    pub async fn cancel(&self, job_id: Uuid) -> usize {
+        {
+            let mut w = self.running.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(running) = w.get_mut(&job_id) {
+                running.queued = None;
+            }
+        }
+        self.in_flight.cancel(job_id)
+    }
+
+    ///
+    /// The jobs that have runs going
+    pub fn running_jobs(&self) -> Vec<Uuid> {
+        self.in_flight.job_ids()
+    }
+
+    ///
+    /// Cancels the runs of all jobs that are going, returns how many runs were cancelled
+    pub async fn cancel_all(&self) -> usize {
+        {
+            let mut w = self.running.lock().unwrap_or_else(|e| e.into_inner());
+            for running in w.values_mut() {
+                running.queued = None;
+            }
+        }
+        self.in_flight.cancel_all()
+    }
+
+    ///
+    /// Forgets the runs that were going, for when their tasks were stopped
+    pub fn reset(&self) {
+        {
+            let mut w = self.running.lock().unwrap_or_else(|e| e.into_inner());
+            w.clear();
+        }
+        self.in_flight.clear();
+    }
+
+    ///
+    /// Limits how many jobs run at the same time, no limit when `None`
+    pub async fn set_max_concurrency(&self, max_concurrency: Option<usize>) {
+        let mut w = self.limits.global.write().await;
+        *w = max_concurrency.map(|max| Arc::new(Semaphore::new(max.min(Semaphore::MAX_PERMITS))));
+    }
+
+    ///
+    /// Limits how many jobs of a concurrency group run at the same time, no limit when `None`
+    pub async fn set_concurrency_limit(&self, group: &str, limit: Option<usize>) {
+        let mut w = self.limits.groups.write().await;
+        match limit {
+            Some(limit) => {
+                let semaphore = Arc::new(Semaphore::new(limit.min(Semaphore::MAX_PERMITS)));
+                w.insert(group.to_string(), semaphore);
+            }
+            None => {
+                w.remove(group);
             }
         }
     }
@@ -66,16 +593,28 @@ impl JobRunner {
         context: &Context,
         job_scheduler: JobsSchedulerLocked,
     ) -> Pin<Box<dyn Future<Output = Result<(), JobSchedulerError>> + Send>> {
-        let job_code = context.job_code.clone();
-        let notify_tx = context.notify_tx.clone();
+        let runs = RunContext {
+            job_code: context.job_code.clone(),
+            storage: context.metadata_storage.clone(),
+            jobs_changed: context.jobs_changed.clone(),
+            tx_notify: context.notify_tx.clone(),
+            job_scheduler,
+            limits: self.limits.clone(),
+            state: context.state.clone(),
+            in_flight: self.in_flight.clone(),
+            tasks: context.tasks.clone(),
+            clock: context.clock.clone(),
+            pending: context.pending_work.clone(),
+        };
+        let running = self.running.clone();
         let job_activation_rx = context.job_activation_tx.subscribe();
+        let tasks = context.tasks.clone();
 
         Box::pin(async move {
-            tokio::spawn(JobRunner::listen_for_activations(
-                job_code,
+            tasks.spawn_listener(JobRunner::listen_for_activations(
+                runs,
+                running,
                 job_activation_rx,
-                notify_tx,
-                job_scheduler,
             ));
             Ok(())
         })
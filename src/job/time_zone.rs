@@ -0,0 +1,118 @@
+use chrono::{DateTime, Duration, LocalResult, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+
+///
+/// Get the next tick of a schedule after the given instant. Without a time zone the schedule is
+/// evaluated in UTC.
+pub fn next_tick_after(
+    schedule: &Schedule,
+    time_zone: Option<&Tz>,
+    after: &DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    match time_zone {
+        Some(time_zone) => next_tick_in_time_zone(schedule, time_zone, after),
+        None => schedule.after(after).next(),
+    }
+}
+
+///
+/// The schedule is evaluated against the wall clock of the time zone. Daylight saving time
+/// transitions are handled as follows:
+///  * A wall-clock time that is skipped (clocks moving forward) fires shifted forward by the
+///    length of the gap, e.g. 02:30 fires at 03:30 when 02:00 jumps to 03:00.
+///  * A wall-clock time that is repeated (clocks moving back) only fires once, on the first
+///    occurrence.
+fn next_tick_in_time_zone(
+    schedule: &Schedule,
+    time_zone: &Tz,
+    after: &DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let mut wall_clock = after.with_timezone(time_zone).naive_local();
+    loop {
+        let candidate = schedule
+            .after(&Utc.from_utc_datetime(&wall_clock))
+            .next()?
+            .naive_utc();
+        let tick = match time_zone.from_local_datetime(&candidate) {
+            LocalResult::Single(tick) => tick.with_timezone(&Utc),
+            LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+            LocalResult::None => {
+                let offset_before_gap = time_zone
+                    .offset_from_utc_datetime(&(candidate - Duration::days(1)))
+                    .fix();
+                let tick =
+                    candidate - Duration::seconds(offset_before_gap.local_minus_utc() as i64);
+                Utc.from_utc_datetime(&tick)
+            }
+        };
+        // Ticks of a repeated hour that already happened are skipped
+        if tick > *after {
+            return Some(tick);
+        }
+        wall_clock = candidate;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe::Amsterdam;
+    use std::str::FromStr;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn without_a_time_zone_the_schedule_is_in_utc() {
+        let schedule = Schedule::from_str("0 30 2 * * *").unwrap();
+        let tick = next_tick_after(&schedule, None, &utc(2024, 3, 30, 12, 0));
+        assert_eq!(tick, Some(utc(2024, 3, 31, 2, 30)));
+    }
+
+    #[test]
+    fn the_wall_clock_of_the_time_zone_is_used() {
+        let schedule = Schedule::from_str("0 30 2 * * *").unwrap();
+        // 02:30 CET is 01:30 UTC
+        let tick = next_tick_after(&schedule, Some(&Amsterdam), &utc(2024, 1, 10, 12, 0));
+        assert_eq!(tick, Some(utc(2024, 1, 11, 1, 30)));
+    }
+
+    #[test]
+    fn a_skipped_time_is_shifted_forward_by_the_gap() {
+        // On 2024-03-31 02:00 CET jumps to 03:00 CEST, so 02:30 runs at 03:30 CEST
+        let schedule = Schedule::from_str("0 30 2 * * *").unwrap();
+        let tick = next_tick_in_time_zone(&schedule, &Amsterdam, &utc(2024, 3, 30, 12, 0));
+        assert_eq!(tick, Some(utc(2024, 3, 31, 1, 30)));
+
+        // The day after it is back at 02:30 CEST
+        let tick = next_tick_in_time_zone(&schedule, &Amsterdam, &tick.unwrap());
+        assert_eq!(tick, Some(utc(2024, 4, 1, 0, 30)));
+    }
+
+    #[test]
+    fn a_repeated_time_fires_once() {
+        // On 2024-10-27 03:00 CEST goes back to 02:00 CET, 02:30 happens twice
+        let schedule = Schedule::from_str("0 30 2 * * *").unwrap();
+        let first = next_tick_in_time_zone(&schedule, &Amsterdam, &utc(2024, 10, 26, 12, 0));
+        // The first occurrence, 02:30 CEST
+        assert_eq!(first, Some(utc(2024, 10, 27, 0, 30)));
+
+        // The second occurrence, 02:30 CET, is skipped
+        let next = next_tick_in_time_zone(&schedule, &Amsterdam, &first.unwrap());
+        assert_eq!(next, Some(utc(2024, 10, 28, 1, 30)));
+
+        // Also when looking from within the repeated hour
+        let next = next_tick_in_time_zone(&schedule, &Amsterdam, &utc(2024, 10, 27, 1, 0));
+        assert_eq!(next, Some(utc(2024, 10, 28, 1, 30)));
+    }
+
+    #[test]
+    fn the_repeated_hour_of_an_hourly_schedule_fires_once() {
+        let schedule = Schedule::from_str("0 0 * * * *").unwrap();
+        // 02:00 CEST, the next wall-clock hour is 03:00 CET
+        let tick = next_tick_in_time_zone(&schedule, &Amsterdam, &utc(2024, 10, 27, 0, 0));
+        assert_eq!(tick, Some(utc(2024, 10, 27, 2, 0)));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::context::Context;
-use crate::job::JobToRunAsync;
+use crate::job::JobToRunFallibleAsync;
 use crate::{JobSchedulerError, OnJobNotification};
 use std::future::Future;
 use std::pin::Pin;
@@ -22,6 +22,6 @@ where
     fn get(&mut self, uuid: Uuid) -> PinnedGetFuture<T>;
 }
 
-pub trait JobCode: ToCode<Box<JobToRunAsync>> + Send {}
+pub trait JobCode: ToCode<Box<JobToRunFallibleAsync>> + Send {}
 
 pub trait NotificationCode: ToCode<Box<OnJobNotification>> {}
@@ -1,21 +1,35 @@
-use crate::context::Context;
+use crate::builder::{JobSchedulerBuilder, DEFAULT_CHANNEL_CAPACITY};
+use crate::clock::Clock;
+use crate::context::{Context, JobActivation};
 use crate::error::JobSchedulerError;
+use crate::job::info::JobInfo;
+#[cfg(not(feature = "has_bytes"))]
+use crate::job::job_data::JobState;
+#[cfg(feature = "has_bytes")]
+use crate::job::job_data_prost::JobState;
+use crate::job::misfire::{JobTicks, MisfirePolicy};
+use crate::job::time_zone::next_tick_after;
 use crate::job::to_code::{JobCode, NotificationCode};
 use crate::job::{JobCreator, JobDeleter, JobLocked, JobRunner};
-use crate::notification::{NotificationCreator, NotificationDeleter, NotificationRunner};
+use crate::notification::{
+    JobNotificationDetails, NotificationCreator, NotificationDeleter, NotificationRunner,
+};
+use crate::registry::OrphanPolicy;
 use crate::scheduler::Scheduler;
 use crate::simple::{
     SimpleJobCode, SimpleMetadataStore, SimpleNotificationCode, SimpleNotificationStore,
 };
 use crate::store::{MetaDataStorage, NotificationStore};
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use std::convert::TryInto;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 #[cfg(feature = "signal")]
 use tokio::signal::unix::SignalKind;
-use tokio::sync::RwLock;
-use tracing::{error, info};
+use tokio::sync::{RwLock, Semaphore};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 pub type ShutdownNotification =
@@ -33,6 +47,11 @@ pub struct JobsSchedulerLocked {
     pub notification_runner: Arc<RwLock<NotificationRunner>>,
     pub scheduler: Arc<RwLock<Scheduler>>,
     pub shutdown_notifier: Option<Arc<RwLock<Box<ShutdownNotification>>>>,
+    /// The misfire policy of jobs that are added without one
+    pub default_misfire_policy: MisfirePolicy,
+    /// What the scheduler does with the jobs in the metadata store that have no code when it's
+    /// initialized, by the first `add` or `start`
+    pub orphan_policy: OrphanPolicy,
 }
 
 impl Clone for JobsSchedulerLocked {
@@ -48,6 +67,8 @@ impl Clone for JobsSchedulerLocked {
             notification_runner: self.notification_runner.clone(),
             scheduler: self.scheduler.clone(),
             shutdown_notifier: self.shutdown_notifier.clone(),
+            default_misfire_policy: self.default_misfire_policy,
+            orphan_policy: self.orphan_policy,
         }
     }
 }
@@ -58,6 +79,8 @@ impl JobsSchedulerLocked {
         notification_storage: Arc<RwLock<Box<dyn NotificationStore + Send + Sync>>>,
         job_code: Arc<RwLock<Box<dyn JobCode + Send + Sync>>>,
         notify_code: Arc<RwLock<Box<dyn NotificationCode + Send + Sync>>>,
+        channel_capacity: usize,
+        clock: Option<Arc<dyn Clock>>,
     ) -> Result<Arc<Context>, JobSchedulerError> {
         {
             let mut metadata_storage = metadata_storage.write().await;
@@ -67,19 +90,15 @@ impl JobsSchedulerLocked {
             let mut notification_storage = notification_storage.write().await;
             notification_storage.init().await?;
         }
-        let context = Context::new(
+        let mut context = Context::new_with_channel_capacity(
             metadata_storage,
             notification_storage,
-            job_code.clone(),
-            notify_code.clone(),
+            job_code,
+            notify_code,
+            channel_capacity,
         );
-        {
-            let mut job_code = job_code.write().await;
-            job_code.init(&context).await?;
-        }
-        {
-            let mut notification_code = notify_code.write().await;
-            notification_code.init(&context).await?;
+        if let Some(clock) = clock {
+            context.clock = clock;
         }
         Ok(Arc::new(context))
     }
@@ -98,6 +117,17 @@ impl JobsSchedulerLocked {
             ..
         } = self;
 
+        // The code is initialized with the actors, so it listens again after a restart
+        {
+            let mut job_code = context.job_code.write().await;
+            job_code.init(&context).await?;
+        }
+
+        {
+            let mut notification_code = context.notification_code.write().await;
+            notification_code.init(&context).await?;
+        }
+
         {
             let job_creator = job_creator.write().await;
             job_creator.init(&context).await?;
@@ -146,17 +176,33 @@ impl JobsSchedulerLocked {
     ///
     /// Initialize the actors
     pub async fn init(&mut self) -> Result<(), JobSchedulerError> {
-        if self.inited().await {
+        // Held until it's done, calls that come in the meantime wait instead of initializing again
+        let mut inited = self.inited.write().await;
+        if *inited {
             return Ok(());
         }
-        {
-            let mut w = self.inited.write().await;
-            *w = true;
+        let init = async {
+            self.clone()
+                .init_actors()
+                .await
+                .map_err(|_| JobSchedulerError::CantInit)?;
+            // Jobs loaded from a persistent store may have no code. They're handled before jobs
+            // are added, whose code may not be there yet when `add` returns.
+            self.orphans(self.orphan_policy).await
+        };
+        if let Err(e) = init.await {
+            // Nothing ticked yet, the actors that were started are stopped so the next call
+            // starts over
+            {
+                let mut scheduler = self.scheduler.write().await;
+                scheduler.shutdown().await;
+            }
+            self.context.tasks.stop().await;
+            self.reset_actors().await;
+            return Err(e);
         }
-        self.clone()
-            .init_actors()
-            .await
-            .map_err(|_| JobSchedulerError::CantInit)
+        *inited = true;
+        Ok(())
     }
 
     ///
@@ -184,6 +230,8 @@ impl JobsSchedulerLocked {
             notification_storage,
             job_code,
             notify_code,
+            DEFAULT_CHANNEL_CAPACITY,
+            None,
         )
         .await
         .map_err(|_| JobSchedulerError::CantInit)?;
@@ -199,6 +247,8 @@ impl JobsSchedulerLocked {
             notification_runner: Arc::new(Default::default()),
             scheduler: Arc::new(Default::default()),
             shutdown_notifier: None,
+            default_misfire_policy: MisfirePolicy::default(),
+            orphan_policy: OrphanPolicy::default(),
         };
 
         Ok(val)
@@ -212,6 +262,31 @@ impl JobsSchedulerLocked {
         notification_storage: Box<dyn NotificationStore + Send + Sync>,
         job_code: Box<dyn JobCode + Send + Sync>,
         notification_code: Box<dyn NotificationCode + Send + Sync>,
+    ) -> Result<Self, JobSchedulerError> {
+        JobsSchedulerLocked::new_with_options(
+            metadata_storage,
+            notification_storage,
+            job_code,
+            notification_code,
+            DEFAULT_CHANNEL_CAPACITY,
+            None,
+        )
+        .await
+    }
+
+    ///
+    /// Create a new `JobSchedulerBuilder` to configure a `JobsSchedulerLocked` with
+    pub fn builder() -> JobSchedulerBuilder {
+        JobSchedulerBuilder::default()
+    }
+
+    pub(crate) async fn new_with_options(
+        metadata_storage: Box<dyn MetaDataStorage + Send + Sync>,
+        notification_storage: Box<dyn NotificationStore + Send + Sync>,
+        job_code: Box<dyn JobCode + Send + Sync>,
+        notification_code: Box<dyn NotificationCode + Send + Sync>,
+        channel_capacity: usize,
+        clock: Option<Arc<dyn Clock>>,
     ) -> Result<Self, JobSchedulerError> {
         let metadata_storage = Arc::new(RwLock::new(metadata_storage));
         let notification_storage = Arc::new(RwLock::new(notification_storage));
@@ -223,6 +298,8 @@ impl JobsSchedulerLocked {
             notification_storage,
             job_code,
             notification_code,
+            channel_capacity,
+            clock,
         )
         .await?;
 
@@ -237,6 +314,8 @@ impl JobsSchedulerLocked {
             notification_runner: Arc::new(Default::default()),
             scheduler: Arc::new(Default::default()),
             shutdown_notifier: None,
+            default_misfire_policy: MisfirePolicy::default(),
+            orphan_policy: OrphanPolicy::default(),
         };
 
         Ok(val)
@@ -251,13 +330,17 @@ impl JobsSchedulerLocked {
     ///     println!("I get executed every 10 seconds!");
     /// })).await;
     /// ```
-    pub async fn add(&self, job: JobLocked) -> Result<Uuid, JobSchedulerError> {
+    pub async fn add(&self, mut job: JobLocked) -> Result<Uuid, JobSchedulerError> {
         let guid = job.guid();
         if !self.inited().await {
             info!("Uninited");
             let mut s = self.clone();
             s.init().await?;
         }
+        job.set_first_tick_from(self.context.clock.now())?;
+        if job.job_data()?.misfire_policy().is_none() {
+            job.set_misfire_policy(self.default_misfire_policy)?;
+        }
 
         let context = self.context.clone();
         JobCreator::add(&context, job).await?;
@@ -266,6 +349,31 @@ impl JobsSchedulerLocked {
         Ok(guid)
     }
 
+    /// Add a job, or replace the job that has the same id. The last tick, the run count and
+    /// whether it's paused are taken over from the job that is replaced. Together with
+    /// `Job::with_id` this lets a service register its jobs on every start without duplicating
+    /// them in a persistent store.
+    pub async fn add_or_replace(&self, mut job: JobLocked) -> Result<Uuid, JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        let existing = {
+            let mut r = self.context.metadata_storage.write().await;
+            r.get(job.guid()).await?
+        };
+        if let Some(existing) = existing {
+            let mut job_data = job.job_data()?;
+            job_data.last_tick = existing.last_tick;
+            job_data.last_tick_nanos = existing.last_tick_nanos;
+            job_data.count = existing.count;
+            job_data.ran = existing.ran;
+            job_data.stopped = existing.stopped;
+            job.set_job_data(job_data)?;
+        }
+        self.add(job).await
+    }
+
     /// Remove a job from the `JobScheduler`
     ///
     /// ```rust,ignore
@@ -289,9 +397,202 @@ impl JobsSchedulerLocked {
         JobDeleter::remove(&context, to_be_removed).await
     }
 
-    /// The `start` spawns a Tokio task where it loops. Every 500ms it
-    /// runs the tick method to increment any
-    /// any pending jobs.
+    /// Remove a job like `remove` does, and cancel its runs that are going
+    pub async fn remove_and_cancel(&self, to_be_removed: &Uuid) -> Result<(), JobSchedulerError> {
+        self.remove(to_be_removed).await?;
+        self.cancel_running(*to_be_removed).await;
+        Ok(())
+    }
+
+    /// Cancel the runs of a job that are going, and a run that is queued after them. The runs
+    /// send a `Cancelled` notification and aren't retried. An async job is dropped at its next
+    /// await, a sync job has to check `JobContext::is_cancelled` to stop early.
+    /// Returns how many runs were cancelled.
+    pub async fn cancel_running(&self, job_id: Uuid) -> usize {
+        let runner = self.job_runner.read().await;
+        runner.cancel(job_id).await
+    }
+
+    /// Pause a job. The scheduler doesn't run it until it is resumed, which survives a restart
+    /// when the store is persistent. A run that already started isn't affected. Sends a `Stop`
+    /// notification.
+    pub async fn pause(&self, job_id: Uuid) -> Result<(), JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        self.pause_job(job_id).await
+    }
+
+    async fn pause_job(&self, job_id: Uuid) -> Result<(), JobSchedulerError> {
+        {
+            let mut w = self.context.metadata_storage.write().await;
+            let mut job = w.get(job_id).await?.ok_or(JobSchedulerError::GetJobData)?;
+            if job.stopped {
+                return Ok(());
+            }
+            job.stopped = true;
+            w.add_or_update(job).await?;
+        }
+        if let Err(e) = self
+            .context
+            .pending_work
+            .send(
+                job_id,
+                &self.context.notify_tx,
+                (job_id, JobState::Stop, Default::default()),
+            )
+            .await
+        {
+            error!("Error sending stop notification {:?}", e);
+        }
+        self.context.jobs_changed.notify_one();
+        Ok(())
+    }
+
+    /// Resume a paused job. Ticks that were missed while it was paused are skipped, so it
+    /// continues with its next tick from now on. A one shot job that became due while it was
+    /// paused runs right away.
+    pub async fn resume(&self, job_id: Uuid) -> Result<(), JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        {
+            let mut w = self.context.metadata_storage.write().await;
+            let mut job = w.get(job_id).await?.ok_or(JobSchedulerError::GetJobData)?;
+            if !job.stopped {
+                return Ok(());
+            }
+            job.stopped = false;
+            let now = self.context.clock.now();
+            let schedule = job.schedule();
+            let time_zone = job.time_zone();
+            let next_tick = job.next_tick_utc().and_then(|next_tick| {
+                JobTicks {
+                    job_type: job.job_type(),
+                    schedule: schedule.as_ref(),
+                    time_zone: time_zone.as_ref(),
+                    repeated_every: job.repeated_every_duration(),
+                    next_tick,
+                }
+                .resumed_tick(&now)
+            });
+            job.set_next_tick(next_tick);
+            w.add_or_update(job).await?;
+        }
+        self.context.jobs_changed.notify_one();
+        Ok(())
+    }
+
+    /// Run a job right away, besides its schedule. The run goes through the overlap policy and
+    /// concurrency limits like any other, and its notifications have `manual` set in their
+    /// `JobNotificationDetails`. The next and last tick of the job are left as they are, and a
+    /// paused job runs as well.
+    pub async fn run_now(&self, job_id: Uuid) -> Result<(), JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        {
+            let mut w = self.context.metadata_storage.write().await;
+            w.get(job_id).await?.ok_or(JobSchedulerError::GetJobData)?;
+        }
+        let details = JobNotificationDetails::run(true);
+        if let Err(e) = self
+            .context
+            .pending_work
+            .send(
+                job_id,
+                &self.context.notify_tx,
+                (job_id, JobState::Scheduled, details),
+            )
+            .await
+        {
+            error!("Error sending notification activation {:?}", e);
+        }
+        let activation = JobActivation {
+            job_id,
+            scheduled_at: self.context.clock.now(),
+            manual: true,
+        };
+        if let Err(e) = self
+            .context
+            .pending_work
+            .send(job_id, &self.context.job_activation_tx, activation)
+            .await
+        {
+            error!("Error sending job activation tx {:?}", e);
+            return Err(JobSchedulerError::CantRunNow);
+        }
+        Ok(())
+    }
+
+    /// Change the schedule of a cron job, keeping its id, notifications and history. The job runs
+    /// next at the first tick of the new schedule from now on, in the job's time zone.
+    ///
+    /// ```rust,ignore
+    /// sched.update_schedule(job_id, "0 30 2 * * *").await?;
+    /// ```
+    pub async fn update_schedule<S, E>(
+        &self,
+        job_id: Uuid,
+        schedule: S,
+    ) -> Result<(), JobSchedulerError>
+    where
+        S: TryInto<Schedule, Error = E>,
+        E: std::error::Error + 'static,
+    {
+        let schedule: Schedule = schedule
+            .try_into()
+            .map_err(|_| JobSchedulerError::ParseSchedule)?;
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        {
+            let mut w = self.context.metadata_storage.write().await;
+            let mut job = w.get(job_id).await?.ok_or(JobSchedulerError::GetJobData)?;
+            job.set_schedule(&schedule)?;
+            let next_tick = next_tick_after(
+                &schedule,
+                job.time_zone().as_ref(),
+                &self.context.clock.now(),
+            );
+            job.set_next_tick(next_tick);
+            w.add_or_update(job).await?;
+        }
+        self.context.jobs_changed.notify_one();
+        Ok(())
+    }
+
+    /// Change the interval of a repeated job, keeping its id, notifications and history. The job
+    /// runs next one interval from now.
+    pub async fn update_interval(
+        &self,
+        job_id: Uuid,
+        every: std::time::Duration,
+    ) -> Result<(), JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        {
+            let mut w = self.context.metadata_storage.write().await;
+            let mut job = w.get(job_id).await?.ok_or(JobSchedulerError::GetJobData)?;
+            job.set_repeated_every(every)?;
+            let next_tick = chrono::Duration::from_std(every)
+                .ok()
+                .and_then(|every| self.context.clock.now().checked_add_signed(every));
+            job.set_next_tick(next_tick);
+            w.add_or_update(job).await?;
+        }
+        self.context.jobs_changed.notify_one();
+        Ok(())
+    }
+
+    /// The `start` spawns a Tokio task where it loops. It sleeps until the next job is due, or
+    /// until jobs are added or removed, and then runs any pending jobs.
     ///
     /// ```rust,ignore
     /// if let Err(e) = sched.start().await {
@@ -304,6 +605,11 @@ impl JobsSchedulerLocked {
             s.init().await?;
         }
         let mut scheduler = self.scheduler.write().await;
+        // After a `shutdown` only the tick loop is gone
+        if scheduler.is_shut_down().await {
+            scheduler.reset();
+            scheduler.init(&self.context).await;
+        }
         let ret = scheduler.start().await;
 
         match ret {
@@ -315,6 +621,71 @@ impl JobsSchedulerLocked {
         }
     }
 
+    /// Set how long the scheduler sleeps at most before it checks the store for due jobs. Jobs
+    /// added or removed through this scheduler wake it up right away, so this only matters for
+    /// changes made to a shared store by something else. Defaults to 5 seconds.
+    pub async fn set_polling_interval(
+        &mut self,
+        polling_interval: std::time::Duration,
+    ) -> Result<(), JobSchedulerError> {
+        if polling_interval.is_zero() {
+            return Err(JobSchedulerError::InvalidPollingInterval);
+        }
+        let mut scheduler = self.scheduler.write().await;
+        scheduler.set_polling_interval(polling_interval).await;
+        Ok(())
+    }
+
+    /// Cap how many sync jobs, created with `Job::new` and the like, run at the same time. Runs
+    /// that are over the cap wait for a running one to finish. This is a limit on jobs, not the size
+    /// of a thread pool: sync jobs run on tokio's blocking thread pool, which the scheduler doesn't
+    /// own, and they share it with everything else on the runtime that uses `spawn_blocking`. That
+    /// pool is sized on the runtime, with `tokio::runtime::Builder::max_blocking_threads`.
+    /// A sync run can't be interrupted, so one that timed out or was cancelled keeps its thread,
+    /// its place under this limit and its turn among the runs of its job until it's done.
+    pub async fn set_max_blocking_jobs(
+        &mut self,
+        max_blocking_jobs: usize,
+    ) -> Result<(), JobSchedulerError> {
+        if max_blocking_jobs == 0 {
+            return Err(JobSchedulerError::InvalidMaxBlockingJobs);
+        }
+        let mut w = self.context.blocking_jobs.write().await;
+        *w = Some(Arc::new(Semaphore::new(
+            max_blocking_jobs.min(Semaphore::MAX_PERMITS),
+        )));
+        Ok(())
+    }
+
+    /// Set how many jobs may run at the same time. Runs over the limit wait in the order they were
+    /// due, and send a `Delayed` notification. There's no limit by default.
+    pub async fn set_max_concurrency(
+        &mut self,
+        max_concurrency: usize,
+    ) -> Result<(), JobSchedulerError> {
+        if max_concurrency == 0 {
+            return Err(JobSchedulerError::InvalidConcurrencyLimit);
+        }
+        let runner = self.job_runner.read().await;
+        runner.set_max_concurrency(Some(max_concurrency)).await;
+        Ok(())
+    }
+
+    /// Set how many jobs of a concurrency group, set with `Job::set_concurrency_group`, may run at
+    /// the same time. The runs count towards the max concurrency of the scheduler as well.
+    pub async fn set_concurrency_limit(
+        &mut self,
+        group: &str,
+        limit: usize,
+    ) -> Result<(), JobSchedulerError> {
+        if limit == 0 {
+            return Err(JobSchedulerError::InvalidConcurrencyLimit);
+        }
+        let runner = self.job_runner.read().await;
+        runner.set_concurrency_limit(group, Some(limit)).await;
+        Ok(())
+    }
+
     /// The `time_till_next_job` method returns the duration till the next job
     /// is supposed to run. This can be used to sleep until then without waking
     /// up at a fixed interval.AsMut
@@ -329,7 +700,7 @@ impl JobsSchedulerLocked {
         let metadata = self.context.metadata_storage.clone();
 
         let mut metadata = metadata.write().await;
-        let ret = metadata.time_till_next_job().await;
+        let ret = metadata.time_till_next_job(self.context.clock.now()).await;
 
         match ret {
             Ok(ret) => Ok(ret),
@@ -351,12 +722,152 @@ impl JobsSchedulerLocked {
             s.init().await?;
         }
         let mut r = self.context.metadata_storage.write().await;
-        r.get(job_id).await.map(|v| {
-            v.map(|vv| vv.next_tick)
-                .filter(|t| *t != 0)
-                .map(|ts| NaiveDateTime::from_timestamp(ts as i64, 0))
-                .map(|ts| DateTime::from_utc(ts, Utc))
-        })
+        r.get(job_id)
+            .await
+            .map(|v| v.and_then(|vv| vv.next_tick_utc()))
+    }
+
+    /// `list_jobs` returns what the metadata store has on every job, in no particular order
+    pub async fn list_jobs(&self) -> Result<Vec<JobInfo>, JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        let mut r = self.context.metadata_storage.write().await;
+        let jobs = r.list_jobs().await?;
+        Ok(jobs.iter().map(JobInfo::from).collect())
+    }
+
+    /// `get_job` returns what the metadata store has on a job, `None` when there's no such job
+    pub async fn get_job(&self, job_id: Uuid) -> Result<Option<JobInfo>, JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        let mut r = self.context.metadata_storage.write().await;
+        let job = r.get(job_id).await?;
+        Ok(job.as_ref().map(JobInfo::from))
+    }
+
+    /// `find_by_name` returns the jobs with the given name
+    pub async fn find_by_name(&self, name: &str) -> Result<Vec<JobInfo>, JobSchedulerError> {
+        let jobs = self.list_jobs().await?;
+        Ok(jobs
+            .into_iter()
+            .filter(|job| job.name.as_deref() == Some(name))
+            .collect())
+    }
+
+    /// `list_by_tag` returns the jobs that have the given tag
+    pub async fn list_by_tag(&self, tag: &str) -> Result<Vec<JobInfo>, JobSchedulerError> {
+        let jobs = self.list_jobs().await?;
+        Ok(jobs
+            .into_iter()
+            .filter(|job| job.tags.iter().any(|t| t == tag))
+            .collect())
+    }
+
+    /// Remove the jobs that have the given tag, returns the ids of the removed jobs
+    pub async fn remove_by_tag(&self, tag: &str) -> Result<Vec<Uuid>, JobSchedulerError> {
+        let jobs = self.list_by_tag(tag).await?;
+        let mut removed = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            self.remove(&job.id).await?;
+            removed.push(job.id);
+        }
+        Ok(removed)
+    }
+
+    /// Look for jobs in the metadata store that have no code, for instance jobs that were loaded
+    /// from a persistent store after a restart and whose kind isn't registered in the
+    /// `JobRegistry`. Each one is logged, and paused or removed depending on the policy. Returns
+    /// the ids of the jobs without code. The scheduler does this with its `orphan_policy` when it's
+    /// initialized.
+    pub async fn handle_orphans(
+        &self,
+        policy: OrphanPolicy,
+    ) -> Result<Vec<Uuid>, JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        self.orphans(policy).await
+    }
+
+    /// Handles the jobs without code, also while the scheduler is being initialized
+    async fn orphans(&self, policy: OrphanPolicy) -> Result<Vec<Uuid>, JobSchedulerError> {
+        let jobs = {
+            let mut r = self.context.metadata_storage.write().await;
+            r.list_jobs().await?
+        };
+        let mut orphans = Vec::new();
+        for job in jobs.iter().map(JobInfo::from) {
+            let code = {
+                let mut w = self.context.job_code.write().await;
+                w.get(job.id).await?
+            };
+            if code.is_some() {
+                continue;
+            }
+            warn!(
+                "Job {:?} named {:?} of kind {:?} has no code",
+                job.id, job.name, job.job_kind
+            );
+            match policy {
+                OrphanPolicy::Report => {}
+                OrphanPolicy::Pause => self.pause_job(job.id).await?,
+                OrphanPolicy::Delete => JobDeleter::remove(&self.context, &job.id).await?,
+            }
+            orphans.push(job.id);
+        }
+        Ok(orphans)
+    }
+
+    /// `payload` returns the payload a job was given with `Job::with_payload`
+    #[cfg(feature = "serde")]
+    pub async fn payload<T>(&self, job_id: Uuid) -> Result<Option<T>, JobSchedulerError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        let job = {
+            let mut r = self.context.metadata_storage.write().await;
+            r.get(job_id).await?
+        };
+        match job {
+            Some(job) => job.payload(),
+            None => Ok(None),
+        }
+    }
+
+    /// `set_state` registers state the jobs can get with `JobContext::state`, like a connection
+    /// pool. There's one value per type, setting a value of a type again replaces it.
+    pub fn set_state<T>(&self, state: T)
+    where
+        T: std::any::Any + Send + Sync,
+    {
+        let mut w = self
+            .context
+            .state
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        w.insert(std::any::TypeId::of::<T>(), Arc::new(state));
+    }
+
+    /// `timeout_for_job` returns how long a run of a job may take before it is cancelled
+    pub async fn timeout_for_job(
+        &mut self,
+        job_id: Uuid,
+    ) -> Result<Option<std::time::Duration>, JobSchedulerError> {
+        if !self.inited().await {
+            let mut s = self.clone();
+            s.init().await?;
+        }
+        let mut r = self.context.metadata_storage.write().await;
+        r.get(job_id).await.map(|v| v.and_then(|vv| vv.timeout()))
     }
 
     ///
@@ -375,6 +886,110 @@ impl JobsSchedulerLocked {
         Ok(())
     }
 
+    ///
+    /// Shut the scheduler down and wait for the runs of jobs and notifications that are going to
+    /// finish. No more jobs are started, but the ones that were already due still run. The runs
+    /// that aren't done by the timeout are dropped, their jobs are returned. The tasks of the
+    /// scheduler are stopped after that, and the shutdown handler is called. The scheduler can be
+    /// started again with `start`.
+    pub async fn shutdown_graceful(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<Uuid>, JobSchedulerError> {
+        let mut notify = None;
+        std::mem::swap(&mut self.shutdown_notifier, &mut notify);
+
+        let cut_off = self.drain_and_stop(timeout).await;
+
+        if let Some(notify) = notify {
+            let mut notify = notify.write().await;
+            notify().await;
+        }
+        Ok(cut_off)
+    }
+
+    ///
+    /// Stop the scheduler so it can be started again with `start`, for instance to put a service
+    /// in maintenance. It stops starting jobs, waits for the runs and notifications that are going
+    /// to finish, and stops its tasks. The jobs and their code are kept, and are scheduled again
+    /// once the scheduler is started. The shutdown handler isn't called.
+    ///
+    /// The runs that aren't done by the timeout are cut off, their jobs are returned. When `stop`
+    /// returns none of them is tracked by the scheduler anymore: they send no further
+    /// notifications, and don't count for the overlap policy of their job once it's started again.
+    /// An async run is dropped at its next await. A sync run can't be interrupted, it only has its
+    /// cancellation token cancelled, and keeps its thread, its place under `max_blocking_jobs` and
+    /// its turn among the runs of its job until it returns.
+    pub async fn stop(
+        &mut self,
+        timeout: std::time::Duration,
+    ) -> Result<Vec<Uuid>, JobSchedulerError> {
+        if !self.inited().await {
+            return Ok(vec![]);
+        }
+        Ok(self.drain_and_stop(timeout).await)
+    }
+
+    /// Stops ticking, waits until the timeout for the runs that are going and stops the tasks.
+    /// Returns the jobs of the runs that were cut off.
+    async fn drain_and_stop(&self, timeout: std::time::Duration) -> Vec<Uuid> {
+        {
+            let mut scheduler = self.scheduler.write().await;
+            scheduler.shutdown().await;
+        }
+
+        let tasks = self.context.tasks.clone();
+        let drained = tokio::time::timeout(timeout, tasks.drain(&self.context))
+            .await
+            .is_ok();
+        let cut_off = if drained {
+            vec![]
+        } else {
+            let runner = self.job_runner.read().await;
+            let cut_off = runner.running_jobs();
+            warn!(
+                "Stopping timed out with {} runs going, cutting off jobs {:?}",
+                tasks.running(),
+                cut_off
+            );
+            cut_off
+        };
+        tasks.stop().await;
+        self.reset().await;
+        cut_off
+    }
+
+    /// Gets the scheduler ready to be initialized again once its tasks are stopped
+    async fn reset(&self) {
+        self.reset_actors().await;
+        let mut w = self.inited.write().await;
+        *w = false;
+    }
+
+    /// Clears what the actors kept while they were running, once their tasks are stopped
+    async fn reset_actors(&self) {
+        self.context.tasks.reopen();
+        {
+            let runner = self.job_runner.read().await;
+            runner.reset();
+        }
+        // The activations that were queued when the listeners stopped are gone
+        self.context.pending_work.clear();
+        {
+            let mut scheduler = self.scheduler.write().await;
+            scheduler.reset();
+        }
+    }
+
+    ///
+    /// Shut the scheduler down like `shutdown` does, and cancel the runs that are going
+    pub async fn shutdown_and_cancel(&mut self) -> Result<(), JobSchedulerError> {
+        self.shutdown().await?;
+        let runner = self.job_runner.read().await;
+        runner.cancel_all().await;
+        Ok(())
+    }
+
     ///
     /// Wait for a signal to shut the runtime down with
     #[cfg(feature = "signal")]
@@ -3,6 +3,9 @@
 extern crate num_derive;
 extern crate core;
 
+mod builder;
+mod channel;
+mod clock;
 mod context;
 mod error;
 mod job;
@@ -12,6 +15,7 @@ mod nats;
 mod notification;
 #[cfg(feature = "postgres_storage")]
 mod postgres;
+mod registry;
 mod scheduler;
 mod simple;
 mod store;
@@ -25,6 +29,7 @@ use crate::job::job_data::ListOfUuids;
 #[cfg(feature = "has_bytes")]
 use crate::job::job_data_prost::ListOfUuids;
 use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
 use cron::Schedule;
 #[cfg(not(feature = "has_bytes"))]
 use job::job_data::{JobAndNextTick, JobStoredData, Uuid as JobUuid};
@@ -38,17 +43,34 @@ pub use crate::nats::{NatsMetadataStore, NatsNotificationStore, NatsStore, NatsS
 #[cfg(feature = "postgres_storage")]
 pub use crate::postgres::{PostgresMetadataStore, PostgresNotificationStore, PostgresStore};
 
-pub use context::Context;
+pub use builder::{JobSchedulerBuilder, DEFAULT_CHANNEL_CAPACITY};
+pub use channel::FanOut;
+pub use clock::{Clock, SystemClock};
+pub use context::{BackgroundTasks, Context, JobActivation, PendingJob, PendingWork};
 pub use error::JobSchedulerError;
+pub use job::info::JobInfo;
+pub use job::job_context::{JobContext, SharedState};
 #[cfg(not(feature = "has_bytes"))]
 pub use job::job_data::JobState as JobNotification;
+#[cfg(not(feature = "has_bytes"))]
+pub use job::job_data::JobType;
+#[cfg(not(feature = "has_bytes"))]
+pub use job::job_data::OverlapPolicy;
 #[cfg(feature = "has_bytes")]
 pub use job::job_data_prost::JobState as JobNotification;
+#[cfg(feature = "has_bytes")]
+pub use job::job_data_prost::JobType;
+#[cfg(feature = "has_bytes")]
+pub use job::job_data_prost::OverlapPolicy;
+pub use job::misfire::MisfirePolicy;
+pub use job::retry::RetryPolicy;
 pub use job::to_code::{JobCode, NotificationCode, PinnedGetFuture, ToCode};
 pub use job::JobLocked as Job;
 pub use job::OnJobNotification;
-pub use job::{JobToRun, JobToRunAsync};
+pub use job::{JobResult, JobToRun, JobToRunAsync, JobToRunFallibleAsync, JobToRunWithContext};
 pub use job_scheduler::JobsSchedulerLocked as JobScheduler;
+pub use notification::JobNotificationDetails;
+pub use registry::{JobRegistry, OrphanPolicy};
 pub use store::{MetaDataStorage, NotificationStore};
 
 pub use simple::{
@@ -93,7 +115,14 @@ impl From<&JobUuid> for Uuid {
 
 impl JobAndNextTick {
     pub fn utc(lt: u64) -> DateTime<Utc> {
-        let dt = SystemTime::UNIX_EPOCH.add(Duration::from_secs(lt));
+        JobAndNextTick::utc_with_nanos(lt, 0)
+    }
+
+    ///
+    /// Records stored before sub-second precision was added have no nanoseconds, which is the
+    /// same as zero nanoseconds
+    pub fn utc_with_nanos(lt: u64, nanos: u32) -> DateTime<Utc> {
+        let dt = SystemTime::UNIX_EPOCH.add(Duration::new(lt, nanos));
         let dt: DateTime<Utc> = DateTime::from(dt);
         dt
     }
@@ -101,12 +130,20 @@ impl JobAndNextTick {
     fn next_tick_utc(&self) -> Option<DateTime<Utc>> {
         match self.next_tick {
             0 => None,
-            val => Some(JobAndNextTick::utc(val)),
+            val => Some(JobAndNextTick::utc_with_nanos(val, self.next_tick_nanos)),
         }
     }
 
     fn last_tick_utc(&self) -> Option<DateTime<Utc>> {
-        self.last_tick.map(JobAndNextTick::utc)
+        self.last_tick
+            .map(|lt| JobAndNextTick::utc_with_nanos(lt, self.last_tick_nanos))
+    }
+
+    fn retry_tick_utc(&self) -> Option<DateTime<Utc>> {
+        match self.retry_tick {
+            0 => None,
+            val => Some(JobAndNextTick::utc_with_nanos(val, self.retry_tick_nanos)),
+        }
     }
 }
 
@@ -124,15 +161,30 @@ impl JobStoredData {
             .and_then(|s| Schedule::from_str(s).ok())
     }
 
+    pub fn time_zone(&self) -> Option<Tz> {
+        self.job
+            .as_ref()
+            .and_then(|j| match j {
+                #[cfg(feature = "has_bytes")]
+                job::job_data_prost::job_stored_data::Job::CronJob(cj) => Some(&*cj.time_zone),
+                #[cfg(not(feature = "has_bytes"))]
+                job::job_data::job_stored_data::Job::CronJob(cj) => Some(&*cj.time_zone),
+                _ => None,
+            })
+            .filter(|tz| !tz.is_empty())
+            .and_then(|tz| Tz::from_str(tz).ok())
+    }
+
     pub fn next_tick_utc(&self) -> Option<DateTime<Utc>> {
         match self.next_tick {
             0 => None,
-            val => Some(JobAndNextTick::utc(val)),
+            val => Some(JobAndNextTick::utc_with_nanos(val, self.next_tick_nanos)),
         }
     }
 
     pub fn last_tick_utc(&self) -> Option<DateTime<Utc>> {
-        self.last_tick.map(JobAndNextTick::utc)
+        self.last_tick
+            .map(|lt| JobAndNextTick::utc_with_nanos(lt, self.last_tick_nanos))
     }
 
     pub fn repeated_every(&self) -> Option<u64> {
@@ -148,15 +200,199 @@ impl JobStoredData {
         })
     }
 
+    pub fn repeated_every_duration(&self) -> Option<Duration> {
+        self.job.as_ref().and_then(|jt| match jt {
+            #[cfg(feature = "has_bytes")]
+            job::job_data_prost::job_stored_data::Job::CronJob(_) => None,
+            #[cfg(not(feature = "has_bytes"))]
+            job::job_data::job_stored_data::Job::CronJob(_) => None,
+            #[cfg(feature = "has_bytes")]
+            job::job_data_prost::job_stored_data::Job::NonCronJob(ncj) => {
+                Some(Duration::new(ncj.repeated_every, ncj.repeated_every_nanos))
+            }
+            #[cfg(not(feature = "has_bytes"))]
+            job::job_data::job_stored_data::Job::NonCronJob(ncj) => {
+                Some(Duration::new(ncj.repeated_every, ncj.repeated_every_nanos))
+            }
+        })
+    }
+
+    ///
+    /// Replace the schedule of a cron job, the time zone is kept
+    pub fn set_schedule(&mut self, schedule: &Schedule) -> Result<(), JobSchedulerError> {
+        match self.job.as_mut() {
+            #[cfg(feature = "has_bytes")]
+            Some(job::job_data_prost::job_stored_data::Job::CronJob(cj)) => {
+                cj.schedule = schedule.to_string();
+                Ok(())
+            }
+            #[cfg(not(feature = "has_bytes"))]
+            Some(job::job_data::job_stored_data::Job::CronJob(cj)) => {
+                cj.schedule = schedule.to_string();
+                Ok(())
+            }
+            _ => Err(JobSchedulerError::WrongJobType),
+        }
+    }
+
+    ///
+    /// Replace the interval of a repeated job
+    pub fn set_repeated_every(&mut self, every: Duration) -> Result<(), JobSchedulerError> {
+        match self.job.as_mut() {
+            #[cfg(feature = "has_bytes")]
+            Some(job::job_data_prost::job_stored_data::Job::NonCronJob(ncj)) if ncj.repeating => {
+                ncj.repeated_every = every.as_secs();
+                ncj.repeated_every_nanos = every.subsec_nanos();
+                Ok(())
+            }
+            #[cfg(not(feature = "has_bytes"))]
+            Some(job::job_data::job_stored_data::Job::NonCronJob(ncj)) if ncj.repeating => {
+                ncj.repeated_every = every.as_secs();
+                ncj.repeated_every_nanos = every.subsec_nanos();
+                Ok(())
+            }
+            _ => Err(JobSchedulerError::WrongJobType),
+        }
+    }
+
     pub fn set_next_tick(&mut self, tick: Option<DateTime<Utc>>) {
-        self.next_tick = match tick {
-            Some(t) => t.timestamp() as u64,
-            None => 0,
+        (self.next_tick, self.next_tick_nanos) = match tick {
+            Some(t) => (t.timestamp() as u64, t.timestamp_subsec_nanos()),
+            None => (0, 0),
         }
     }
 
     pub fn set_last_tick(&mut self, tick: Option<DateTime<Utc>>) {
         self.last_tick = tick.map(|t| t.timestamp() as u64);
+        self.last_tick_nanos = tick.map(|t| t.timestamp_subsec_nanos()).unwrap_or(0);
+    }
+
+    pub fn misfire_policy(&self) -> Option<MisfirePolicy> {
+        MisfirePolicy::from_stored(
+            self.misfire,
+            self.misfire_max_runs,
+            self.misfire_grace_period_ms,
+        )
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        match self.timeout_ms {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout_ms = timeout.map(|t| t.as_millis() as u64).unwrap_or(0);
+    }
+
+    pub fn concurrency_group(&self) -> Option<&str> {
+        Some(&*self.concurrency_group).filter(|group| !group.is_empty())
+    }
+
+    pub fn set_concurrency_group(&mut self, group: Option<&str>) {
+        self.concurrency_group = group.unwrap_or_default().to_string();
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        Some(&*self.name).filter(|name| !name.is_empty())
+    }
+
+    pub fn set_name(&mut self, name: Option<&str>) {
+        self.name = name.unwrap_or_default().to_string();
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        Some(&*self.description).filter(|description| !description.is_empty())
+    }
+
+    pub fn set_description(&mut self, description: Option<&str>) {
+        self.description = description.unwrap_or_default().to_string();
+    }
+
+    pub fn job_kind(&self) -> Option<&str> {
+        Some(&*self.job_kind).filter(|kind| !kind.is_empty())
+    }
+
+    pub fn set_job_kind(&mut self, kind: Option<&str>) {
+        self.job_kind = kind.unwrap_or_default().to_string();
+    }
+
+    ///
+    /// The payload of the job, `None` when it has none
+    #[cfg(feature = "serde")]
+    pub fn payload<T>(&self) -> Result<Option<T>, JobSchedulerError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        if self.extra.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_slice(&self.extra)
+            .map(Some)
+            .map_err(|e| JobSchedulerError::Payload(e.to_string()))
+    }
+
+    ///
+    /// The payload is stored as JSON in `extra`
+    #[cfg(feature = "serde")]
+    pub fn set_payload<T>(&mut self, payload: &T) -> Result<(), JobSchedulerError>
+    where
+        T: serde::Serialize,
+    {
+        self.extra =
+            serde_json::to_vec(payload).map_err(|e| JobSchedulerError::Payload(e.to_string()))?;
+        Ok(())
+    }
+
+    ///
+    /// The tags are kept sorted and without duplicates
+    pub fn set_tags(&mut self, tags: &[&str]) {
+        let mut tags = tags.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        tags.sort();
+        tags.dedup();
+        self.tags = tags;
+    }
+
+    pub fn retry_policy(&self) -> Option<RetryPolicy> {
+        RetryPolicy::from_stored(
+            self.retry_max_attempts,
+            self.retry_initial_backoff_ms,
+            self.retry_multiplier,
+            self.retry_max_backoff_ms,
+            self.retry_jitter,
+        )
+    }
+
+    pub fn set_retry_policy(&mut self, policy: Option<RetryPolicy>) {
+        let (max_attempts, initial_backoff_ms, multiplier, max_backoff_ms, jitter) =
+            RetryPolicy::to_stored(policy.as_ref());
+        self.retry_max_attempts = max_attempts;
+        self.retry_initial_backoff_ms = initial_backoff_ms;
+        self.retry_multiplier = multiplier;
+        self.retry_max_backoff_ms = max_backoff_ms;
+        self.retry_jitter = jitter;
+    }
+
+    pub fn retry_tick_utc(&self) -> Option<DateTime<Utc>> {
+        match self.retry_tick {
+            0 => None,
+            val => Some(JobAndNextTick::utc_with_nanos(val, self.retry_tick_nanos)),
+        }
+    }
+
+    pub fn set_retry_tick(&mut self, tick: Option<DateTime<Utc>>) {
+        (self.retry_tick, self.retry_tick_nanos) = match tick {
+            Some(t) => (t.timestamp() as u64, t.timestamp_subsec_nanos()),
+            None => (0, 0),
+        }
+    }
+
+    pub fn set_misfire_policy(&mut self, policy: Option<MisfirePolicy>) {
+        let (misfire, max_runs, grace_period_ms) = MisfirePolicy::to_stored(policy.as_ref());
+        self.misfire = misfire;
+        self.misfire_max_runs = max_runs;
+        self.misfire_grace_period_ms = grace_period_ms;
     }
 }
 
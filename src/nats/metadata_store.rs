@@ -114,6 +114,7 @@ impl InitStore for NatsMetadataStore {
 impl MetaDataStorage for NatsMetadataStore {
     fn list_next_ticks(
         &mut self,
+        _now: DateTime<Utc>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<JobAndNextTick>, JobSchedulerError>> + Send>> {
         let list_guids = self.list_guids();
         let bucket = self.store.bucket.clone();
@@ -140,6 +141,12 @@ impl MetaDataStorage for NatsMetadataStore {
                     job_type: jd.job_type,
                     next_tick: jd.next_tick,
                     last_tick: jd.last_tick,
+                    next_tick_nanos: jd.next_tick_nanos,
+                    last_tick_nanos: jd.last_tick_nanos,
+                    retry_tick: jd.retry_tick,
+                    retry_tick_nanos: jd.retry_tick_nanos,
+                    retry_attempt: jd.retry_attempt,
+                    stopped: jd.stopped,
                 })
                 .collect::<Vec<_>>();
             Ok(list)
@@ -158,11 +165,44 @@ impl MetaDataStorage for NatsMetadataStore {
             let get = get.await;
             match get {
                 Ok(Some(mut val)) => {
-                    val.next_tick = match next_tick {
-                        Some(next_tick) => next_tick.timestamp(),
-                        None => 0,
-                    } as u64;
-                    val.last_tick = last_tick.map(|lt| lt.timestamp() as u64);
+                    val.set_next_tick(next_tick);
+                    val.set_last_tick(last_tick);
+                    let bytes = val.encode_to_vec();
+                    let bucket = bucket.read().await;
+                    bucket
+                        .put(&*uuid_to_nats_id(guid), bytes)
+                        .map(|_| ())
+                        .map_err(|e| {
+                            error!("Error updating value {:?}", e);
+                            JobSchedulerError::UpdateJobData
+                        })
+                }
+                Ok(None) => {
+                    error!("Could not get value to update");
+                    Err(JobSchedulerError::UpdateJobData)
+                }
+                Err(e) => {
+                    error!("Could not get value to update {:?}", e);
+                    Err(JobSchedulerError::UpdateJobData)
+                }
+            }
+        })
+    }
+
+    fn set_retry(
+        &mut self,
+        guid: Uuid,
+        retry_tick: Option<DateTime<Utc>>,
+        attempt: u32,
+    ) -> Pin<Box<dyn Future<Output = Result<(), JobSchedulerError>> + Send>> {
+        let get = self.get(guid);
+        let bucket = self.store.bucket.clone();
+        Box::pin(async move {
+            let get = get.await;
+            match get {
+                Ok(Some(mut val)) => {
+                    val.set_retry_tick(retry_tick);
+                    val.retry_attempt = attempt;
                     let bytes = val.encode_to_vec();
                     let bucket = bucket.read().await;
                     bucket
@@ -187,6 +227,7 @@ impl MetaDataStorage for NatsMetadataStore {
 
     fn time_till_next_job(
         &mut self,
+        now: DateTime<Utc>,
     ) -> Pin<Box<dyn Future<Output = Result<Option<Duration>, JobSchedulerError>> + Send>> {
         let list = self.list_guids();
         let bucket = self.store.bucket.clone();
@@ -198,8 +239,6 @@ impl MetaDataStorage for NatsMetadataStore {
             }
             let list = list.unwrap();
             let bucket = bucket.read().await;
-            let now = Utc::now();
-            let now = now.timestamp() as u64;
             let ret = list
                 .uuids
                 .iter()
@@ -210,19 +249,40 @@ impl MetaDataStorage for NatsMetadataStore {
                 .flat_map(|uuid| bucket.get(&*uuid_to_nats_id(uuid)))
                 .flatten()
                 .flat_map(|b| JobStoredData::decode(b.as_slice()))
-                .filter_map(|jd| match jd.next_tick {
-                    0 => None,
-                    i => {
-                        if i > now {
-                            Some(i)
-                        } else {
-                            None
-                        }
-                    }
-                })
+                .filter(|jd| !jd.stopped)
+                .flat_map(|jd| [jd.next_tick_utc(), jd.retry_tick_utc()])
+                .flatten()
+                .filter(|next_tick| *next_tick > now)
                 .min()
-                .map(|t| t - now)
-                .map(std::time::Duration::from_secs);
+                .and_then(|next_tick| (next_tick - now).to_std().ok());
+            Ok(ret)
+        })
+    }
+
+    fn list_jobs(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<JobStoredData>, JobSchedulerError>> + Send>> {
+        let list = self.list_guids();
+        let bucket = self.store.bucket.clone();
+        Box::pin(async move {
+            let list = list.await;
+            if let Err(e) = list {
+                error!("Could not get list of guids {:?}", e);
+                return Err(JobSchedulerError::CantListJobs);
+            }
+            let list = list.unwrap();
+            let bucket = bucket.read().await;
+            let ret = list
+                .uuids
+                .iter()
+                .map(|uuid| {
+                    let uuid: Uuid = uuid.into();
+                    uuid
+                })
+                .flat_map(|uuid| bucket.get(&*uuid_to_nats_id(uuid)))
+                .flatten()
+                .flat_map(|b| JobStoredData::decode(b.as_slice()))
+                .collect::<Vec<_>>();
             Ok(ret)
         })
     }
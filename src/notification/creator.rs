@@ -1,3 +1,4 @@
+use crate::channel::FanOut;
 use crate::context::Context;
 #[cfg(not(feature = "has_bytes"))]
 use crate::job::job_data::{JobState, NotificationData};
@@ -8,7 +9,7 @@ use crate::{JobSchedulerError, OnJobNotification};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::broadcast::{Receiver, Sender};
+use tokio::sync::mpsc::Receiver;
 use tokio::sync::RwLock;
 use tracing::{error, warn};
 use uuid::Uuid;
@@ -20,15 +21,9 @@ impl NotificationCreator {
     async fn listen_for_additions(
         storage: Arc<RwLock<Box<dyn NotificationStore + Send + Sync>>>,
         mut rx: Receiver<(NotificationData, Arc<RwLock<Box<OnJobNotification>>>)>,
-        tx_created: Sender<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+        tx_created: FanOut<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving {:?}", e);
-                break;
-            }
-            let (data, _) = val.unwrap();
+        while let Some((data, _)) = rx.recv().await {
             if data.job_id.is_none() {
                 error!("Empty job id {:?}", data);
                 continue;
@@ -60,13 +55,13 @@ impl NotificationCreator {
             let val = storage.add_or_update(val).await;
             if let Err(e) = val {
                 error!("Error adding or updating {:?}", e);
-                if let Err(e) = tx_created.send(Err((e, Some(notification_id)))) {
+                if let Err(e) = tx_created.send(Err((e, Some(notification_id)))).await {
                     error!("Error sending adding or updating error {:?}", e);
                 }
                 continue;
             }
 
-            if let Err(e) = tx_created.send(Ok(notification_id)) {
+            if let Err(e) = tx_created.send(Ok(notification_id)).await {
                 warn!("Error sending created state {:?}", e);
             }
         }
@@ -79,9 +74,10 @@ impl NotificationCreator {
         let rx = context.notify_create_tx.subscribe();
         let tx_created = context.notify_created_tx.clone();
         let storage = context.notification_storage.clone();
+        let tasks = context.tasks.clone();
 
         Box::pin(async move {
-            tokio::spawn(NotificationCreator::listen_for_additions(
+            tasks.spawn_listener(NotificationCreator::listen_for_additions(
                 storage, rx, tx_created,
             ));
             Ok(())
@@ -110,53 +106,23 @@ impl NotificationCreator {
             extra: vec![],
         };
         let create_tx = context.notify_create_tx.clone();
-        let mut created_rx = context.notify_created_tx.subscribe();
-        let (tx, rx) = std::sync::mpsc::channel();
-
-        tokio::spawn(async move {
-            tokio::spawn(async move {
-                // TODO can maybe not use RwLock
-                if let Err(_e) = create_tx.send((data, Arc::new(RwLock::new(run)))) {
-                    error!("Error sending notification data");
-                }
-            });
-
-            'receiving_additions: loop {
-                let created = created_rx.recv().await;
-                match created {
-                    Ok(e) => match e {
-                        Ok(uuid) => {
-                            if uuid == notification_id {
-                                if let Err(e) = tx.send(Ok(uuid)) {
-                                    error!("Error sending notification addition success {:?}", e);
-                                }
-                                break 'receiving_additions;
-                            }
-                        }
-                        Err((e, Some(uuid))) => {
-                            if uuid == notification_id {
-                                if let Err(e) = tx.send(Err(e)) {
-                                    error!("Error sending notification addition failure {:?}", e);
-                                }
-                                break 'receiving_additions;
-                            }
-                        }
-                        _ => {}
-                    },
-                    Err(e) => {
-                        error!("Error receiving from created {:?}", e);
-                    }
-                }
-            }
+        let mut created_rx = context.notify_created_tx.subscribe_filtered(move |created| {
+            matches!(created, Ok(uuid) | Err((_, Some(uuid))) if *uuid == notification_id)
         });
+        // TODO can maybe not use RwLock
+        if let Err(_e) = create_tx.send((data, Arc::new(RwLock::new(run)))).await {
+            error!("Error sending notification data");
+            return Err(JobSchedulerError::CantAdd);
+        }
 
-        let rx = rx.recv();
-        match rx {
-            Ok(ret) => ret,
-            Err(e) => {
-                error!("Error receiving status from notification addition {:?}", e);
-                Err(JobSchedulerError::CantAdd)
+        while let Some(created) = created_rx.recv().await {
+            match created {
+                Ok(uuid) if uuid == notification_id => return Ok(uuid),
+                Err((e, Some(uuid))) if uuid == notification_id => return Err(e),
+                _ => {}
             }
         }
+        error!("Notification creations stopped");
+        Err(JobSchedulerError::CantAdd)
     }
 }
@@ -1,3 +1,4 @@
+use crate::channel::FanOut;
 use crate::context::{Context, NotificationDeletedResult};
 #[cfg(not(feature = "has_bytes"))]
 use crate::job::job_data::JobState;
@@ -9,7 +10,7 @@ use crate::JobSchedulerError;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::broadcast::{Receiver, Sender};
+use tokio::sync::mpsc::Receiver;
 use tokio::sync::RwLock;
 use tracing::error;
 
@@ -20,15 +21,9 @@ impl NotificationDeleter {
     async fn listen_to_job_removals(
         storage: Arc<RwLock<Box<dyn NotificationStore + Send + Sync>>>,
         mut rx_job_delete: Receiver<JobId>,
-        tx_notification_deleted: Sender<NotificationDeletedResult>,
+        tx_notification_deleted: FanOut<NotificationDeletedResult>,
     ) {
-        loop {
-            let val = rx_job_delete.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving delete jobs {:?}", e);
-                break;
-            }
-            let job_id = val.unwrap();
+        while let Some(job_id) = rx_job_delete.recv().await {
             let mut storage = storage.write().await;
             let guids = storage.list_notification_guids_for_job_id(job_id).await;
             if let Err(e) = guids {
@@ -42,7 +37,10 @@ impl NotificationDeleter {
                     error!("Error deleting notification {:?}", e);
                     continue;
                 }
-                if let Err(e) = tx_notification_deleted.send(Ok((notification_id, true, None))) {
+                if let Err(e) = tx_notification_deleted
+                    .send(Ok((notification_id, true, None)))
+                    .await
+                {
                     error!("Error sending deletion {:?}", e);
                     continue;
                 }
@@ -53,15 +51,9 @@ impl NotificationDeleter {
     async fn listen_for_notification_removals(
         storage: Arc<RwLock<Box<dyn NotificationStore + Send + Sync>>>,
         mut rx: Receiver<(NotificationId, Option<Vec<JobState>>)>,
-        tx_deleted: Sender<NotificationDeletedResult>,
+        tx_deleted: FanOut<NotificationDeletedResult>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving notification removals {:?}", e);
-                break;
-            }
-            let (uuid, states) = val.unwrap();
+        while let Some((uuid, states)) = rx.recv().await {
             {
                 let mut storage = storage.write().await;
                 if let Some(states) = states {
@@ -72,7 +64,8 @@ impl NotificationDeleter {
                             continue;
                         }
                         let delete = delete.unwrap();
-                        if let Err(e) = tx_deleted.send(Ok((uuid, delete, Some(vec![state])))) {
+                        if let Err(e) = tx_deleted.send(Ok((uuid, delete, Some(vec![state])))).await
+                        {
                             error!("Error sending notification deleted state {:?}", e);
                         }
                     }
@@ -82,7 +75,7 @@ impl NotificationDeleter {
                         error!("Error deleting notification for all states {:?}", e);
                         continue;
                     }
-                    if let Err(e) = tx_deleted.send(Ok((uuid, true, None))) {
+                    if let Err(e) = tx_deleted.send(Ok((uuid, true, None))).await {
                         error!("Error sending {:?}", e);
                     }
                 }
@@ -98,14 +91,15 @@ impl NotificationDeleter {
         let rx_notification_delete = context.notify_delete_tx.subscribe();
         let tx_notification_deleted = context.notify_deleted_tx.clone();
         let storage = context.notification_storage.clone();
+        let tasks = context.tasks.clone();
 
         Box::pin(async move {
-            tokio::spawn(NotificationDeleter::listen_to_job_removals(
+            tasks.spawn_listener(NotificationDeleter::listen_to_job_removals(
                 storage.clone(),
                 rx_job_delete,
                 tx_notification_deleted.clone(),
             ));
-            tokio::spawn(NotificationDeleter::listen_for_notification_removals(
+            tasks.spawn_listener(NotificationDeleter::listen_for_notification_removals(
                 storage,
                 rx_notification_delete,
                 tx_notification_deleted,
@@ -114,51 +108,29 @@ impl NotificationDeleter {
         })
     }
 
-    pub fn remove(
+    pub async fn remove(
         context: &Context,
         notification_id: &NotificationId,
         states: Option<Vec<JobState>>,
     ) -> Result<(NotificationId, bool), JobSchedulerError> {
         let notification_id = *notification_id;
         let delete_tx = context.notify_delete_tx.clone();
-        let mut deleted_rx = context.notify_deleted_tx.subscribe();
-        let (tx, rx) = std::sync::mpsc::channel();
-
-        tokio::spawn(async move {
-            tokio::spawn(async move {
-                if let Err(e) = delete_tx.send((notification_id, states)) {
-                    error!("Error sending notification removal {:?}", e);
-                }
-            });
-            while let Ok(val) = deleted_rx.recv().await {
-                match val {
-                    Ok((uuid, deleted, _)) => {
-                        if uuid == notification_id {
-                            if let Err(e) = tx.send(Ok((uuid, deleted))) {
-                                error!("Error sending notification removal success {:?}", e);
-                            }
-                            break;
-                        }
-                    }
-                    Err((e, Some(uuid))) => {
-                        if uuid == notification_id {
-                            if let Err(e) = tx.send(Err(e)) {
-                                error!("Error sending removal error {:?}", e);
-                            }
-                            break;
-                        }
-                    }
-                    _ => {}
-                }
-            }
+        let mut deleted_rx = context.notify_deleted_tx.subscribe_filtered(move |deleted| {
+            matches!(deleted, Ok((uuid, _, _)) | Err((_, Some(uuid))) if *uuid == notification_id)
         });
-        let ret = rx.recv();
-        match ret {
-            Ok(ret) => ret,
-            Err(e) => {
-                error!("Error getting result from notification removal {:?}", e);
-                Err(JobSchedulerError::CantRemove)
+        if let Err(e) = delete_tx.send((notification_id, states)).await {
+            error!("Error sending notification removal {:?}", e);
+            return Err(JobSchedulerError::CantRemove);
+        }
+
+        while let Some(val) = deleted_rx.recv().await {
+            match val {
+                Ok((uuid, deleted, _)) if uuid == notification_id => return Ok((uuid, deleted)),
+                Err((e, Some(uuid))) if uuid == notification_id => return Err(e),
+                _ => {}
             }
         }
+        error!("Notification removals stopped");
+        Err(JobSchedulerError::CantRemove)
     }
 }
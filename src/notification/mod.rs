@@ -12,6 +12,47 @@ pub use deleter::NotificationDeleter;
 pub use runner::NotificationRunner;
 use uuid::Uuid;
 
+tokio::task_local! {
+    static DETAILS: JobNotificationDetails;
+}
+
+///
+/// Details of the state change a notification was run for
+#[derive(Clone, Debug, Default)]
+pub struct JobNotificationDetails {
+    /// Why the run failed, set for `JobNotification::Failed` and the retry notifications after it
+    pub error: Option<String>,
+    /// The attempt that failed, set for `JobNotification::Retrying` and `JobNotification::GaveUp`
+    pub attempt: Option<u32>,
+    /// Whether the run was requested with `JobScheduler::run_now` instead of by the schedule
+    pub manual: bool,
+}
+
+impl JobNotificationDetails {
+    /// The details of the notification that is currently running, `None` outside of a notification
+    pub fn current() -> Option<Self> {
+        DETAILS.try_with(|details| details.clone()).ok()
+    }
+
+    pub(crate) fn run(manual: bool) -> Self {
+        Self {
+            manual,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn failed(&self, error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) async fn scope<F: std::future::Future>(self, f: F) -> F::Output {
+        DETAILS.scope(self, f).await
+    }
+}
+
 impl NotificationData {
     pub fn job_id_and_notification_id_from_data(&self) -> Option<(JobId, NotificationId)> {
         match self.job_id.as_ref() {
@@ -1,15 +1,16 @@
-use crate::context::Context;
+use crate::context::{BackgroundTasks, Context, PendingWork};
 #[cfg(not(feature = "has_bytes"))]
 use crate::job::job_data::JobState;
 #[cfg(feature = "has_bytes")]
 use crate::job::job_data_prost::JobState;
 use crate::job::to_code::NotificationCode;
+use crate::notification::JobNotificationDetails;
 use crate::store::NotificationStore;
 use crate::JobSchedulerError;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::broadcast::Receiver;
+use tokio::sync::mpsc::Receiver;
 use tokio::sync::RwLock;
 use tracing::error;
 use uuid::Uuid;
@@ -20,16 +21,14 @@ pub struct NotificationRunner {}
 impl NotificationRunner {
     async fn listen_for_activations(
         code: Arc<RwLock<Box<dyn NotificationCode + Send + Sync>>>,
-        mut rx: Receiver<(Uuid, JobState)>,
+        mut rx: Receiver<(Uuid, JobState, JobNotificationDetails)>,
         storage: Arc<RwLock<Box<dyn NotificationStore + Send + Sync>>>,
+        tasks: BackgroundTasks,
+        pending: PendingWork,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving value {:?}", e);
-                break;
-            }
-            let (job_id, state) = val.unwrap();
+        while let Some((job_id, state, details)) = rx.recv().await {
+            let _busy = tasks.busy();
+            let _pending = pending.take(job_id);
             let mut storage = storage.write().await;
             let notifications = storage
                 .list_notification_guids_for_job_and_state(job_id, state)
@@ -48,10 +47,11 @@ impl NotificationRunner {
                 match code {
                     Ok(Some(code)) => {
                         let code = code.clone();
-                        tokio::spawn(async move {
+                        let details = details.clone();
+                        tasks.spawn_run(details.scope(async move {
                             let mut code = code.write().await;
                             (code)(job_id, notification_id, state).await;
-                        });
+                        }));
                     }
                     _ => {
                         error!(
@@ -72,11 +72,15 @@ impl NotificationRunner {
         let code = context.notification_code.clone();
         let rx = context.notify_tx.subscribe();
         let storage = context.notification_storage.clone();
+        let tasks = context.tasks.clone();
+        let pending = context.pending_work.clone();
 
         Box::pin(async move {
-            tokio::spawn(NotificationRunner::listen_for_activations(
-                code, rx, storage,
-            ));
+            tasks
+                .clone()
+                .spawn_listener(NotificationRunner::listen_for_activations(
+                    code, rx, storage, tasks, pending,
+                ));
             Ok(())
         })
     }
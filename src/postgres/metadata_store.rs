@@ -13,6 +13,39 @@ use tracing::error;
 use uuid::Uuid;
 
 const TABLE: &str = "job";
+const ADDED_COLUMNS: &[&str] = &[
+    "time_zone TEXT",
+    "misfire INTEGER",
+    "misfire_max_runs INTEGER",
+    "misfire_grace_period_ms BIGINT",
+    "next_tick_nanos INTEGER",
+    "last_tick_nanos INTEGER",
+    "repeated_every_nanos INTEGER",
+    "overlap_policy INTEGER",
+    "timeout_ms BIGINT",
+    "retry_max_attempts INTEGER",
+    "retry_initial_backoff_ms BIGINT",
+    "retry_multiplier DOUBLE PRECISION",
+    "retry_max_backoff_ms BIGINT",
+    "retry_jitter DOUBLE PRECISION",
+    "retry_attempt INTEGER",
+    "retry_tick BIGINT",
+    "retry_tick_nanos INTEGER",
+    "concurrency_group TEXT",
+    "name TEXT",
+    "description TEXT",
+    "tags TEXT[]",
+    "job_kind TEXT",
+];
+/// The columns a `JobStoredData` is read from, in the order `From<Row>` expects them
+const JOB_COLUMNS: &str = "\
+    id, last_updated, next_tick, last_tick, job_type, count, \
+    ran, stopped, schedule, repeating, repeated_every, \
+    extra, time_zone, misfire, misfire_max_runs, misfire_grace_period_ms, \
+    next_tick_nanos, last_tick_nanos, repeated_every_nanos, overlap_policy, \
+    timeout_ms, retry_max_attempts, retry_initial_backoff_ms, retry_multiplier, \
+    retry_max_backoff_ms, retry_jitter, retry_attempt, retry_tick, \
+    retry_tick_nanos, concurrency_group, name, description, tags, job_kind";
 
 #[derive(Clone)]
 pub struct PostgresMetadataStore {
@@ -51,21 +84,18 @@ impl DataStore<JobStoredData> for PostgresMetadataStore {
                 PostgresStore::Created(_) => Err(JobSchedulerError::GetJobData),
                 PostgresStore::Inited(store) => {
                     let store = store.read().await;
-                    let sql = "select \
-                        id, last_updated, next_tick, last_tick, job_type, count, \
-                        ran, stopped, schedule, repeating, repeated_every, \
-                        extra \
-                     from "
-                        .to_string()
+                    let sql = "select ".to_string()
+                        + JOB_COLUMNS
+                        + " from "
                         + &*table
                         + " where id = $1 limit 1";
-                    let row = store.query_one(&*sql, &[&id]).await;
+                    let row = store.query_opt(&*sql, &[&id]).await;
                     if let Err(e) = row {
                         error!("Error getting value {:?}", e);
                         return Err(JobSchedulerError::GetJobData);
                     }
                     let row = row.unwrap();
-                    Ok(Some(row.into()))
+                    Ok(row.map(|row| row.into()))
                 }
             }
         })
@@ -92,12 +122,21 @@ impl DataStore<JobStoredData> for PostgresMetadataStore {
                         + " (\
                         id, last_updated, next_tick, job_type, count, \
                         ran, stopped, schedule, repeating, repeated_every, \
-                        extra, last_tick \
+                        extra, last_tick, time_zone, misfire, misfire_max_runs, \
+                        misfire_grace_period_ms, next_tick_nanos, last_tick_nanos, \
+                        repeated_every_nanos, overlap_policy, timeout_ms, retry_max_attempts, \
+                        retry_initial_backoff_ms, retry_multiplier, retry_max_backoff_ms, \
+                        retry_jitter, retry_attempt, retry_tick, retry_tick_nanos, \
+                        concurrency_group, name, description, tags, job_kind \
                     )\
                     VALUES (\
                         $1, $2, $3, $4, $5, \
                         $6, $7, $8, $9, $10,\
-                        $11, $12 \
+                        $11, $12, $13, $14, $15, \
+                        $16, $17, $18, $19, $20, \
+                        $21, $22, $23, $24, $25, \
+                        $26, $27, $28, $29, $30, \
+                        $31, $32, $33, $34 \
                     )\
                     ON CONFLICT (id) \
                     DO \
@@ -105,7 +144,14 @@ impl DataStore<JobStoredData> for PostgresMetadataStore {
                         SET \
                             last_updated=$2, next_tick=$3, job_type=$4, count=$5, \
                             ran=$6, stopped=$7, schedule=$8, repeating=$9, repeated_every=$10, \
-                            extra=$11, last_tick=$12
+                            extra=$11, last_tick=$12, time_zone=$13, misfire=$14, \
+                            misfire_max_runs=$15, misfire_grace_period_ms=$16, \
+                            next_tick_nanos=$17, last_tick_nanos=$18, repeated_every_nanos=$19, \
+                            overlap_policy=$20, timeout_ms=$21, retry_max_attempts=$22, \
+                            retry_initial_backoff_ms=$23, retry_multiplier=$24, \
+                            retry_max_backoff_ms=$25, retry_jitter=$26, retry_attempt=$27, \
+                            retry_tick=$28, retry_tick_nanos=$29, concurrency_group=$30, \
+                            name=$31, description=$32, tags=$33, job_kind=$34
                     ";
                     let last_updated = data.last_updated.as_ref().map(|i| *i as i64);
                     let next_tick = data.next_tick as i64;
@@ -125,8 +171,40 @@ impl DataStore<JobStoredData> for PostgresMetadataStore {
                         Some(NonCronJobType(ct)) => Some(ct.repeated_every as i64),
                         _ => None,
                     };
+                    let time_zone = match data.job.as_ref() {
+                        Some(CronJobType(ct)) if !ct.time_zone.is_empty() => {
+                            Some(ct.time_zone.clone())
+                        }
+                        _ => None,
+                    };
                     let extra = data.extra;
                     let last_tick = data.last_tick.as_ref().map(|i| *i as i64);
+                    let misfire = data.misfire;
+                    let misfire_max_runs = data.misfire_max_runs as i32;
+                    let misfire_grace_period_ms = data.misfire_grace_period_ms as i64;
+                    let next_tick_nanos = data.next_tick_nanos as i32;
+                    let last_tick_nanos = data.last_tick_nanos as i32;
+                    let overlap_policy = data.overlap_policy;
+                    let timeout_ms = data.timeout_ms as i64;
+                    let retry_max_attempts = data.retry_max_attempts as i32;
+                    let retry_initial_backoff_ms = data.retry_initial_backoff_ms as i64;
+                    let retry_multiplier = data.retry_multiplier;
+                    let retry_max_backoff_ms = data.retry_max_backoff_ms as i64;
+                    let retry_jitter = data.retry_jitter;
+                    let retry_attempt = data.retry_attempt as i32;
+                    let retry_tick = data.retry_tick as i64;
+                    let retry_tick_nanos = data.retry_tick_nanos as i32;
+                    let concurrency_group =
+                        Some(data.concurrency_group.clone()).filter(|group| !group.is_empty());
+                    let name = Some(data.name.clone()).filter(|name| !name.is_empty());
+                    let description = Some(data.description.clone())
+                        .filter(|description| !description.is_empty());
+                    let tags = data.tags.clone();
+                    let job_kind = Some(data.job_kind.clone()).filter(|kind| !kind.is_empty());
+                    let repeated_every_nanos = match data.job.as_ref() {
+                        Some(NonCronJobType(ct)) => Some(ct.repeated_every_nanos as i32),
+                        _ => None,
+                    };
 
                     let val = store
                         .query(
@@ -144,6 +222,28 @@ impl DataStore<JobStoredData> for PostgresMetadataStore {
                                 &repeated_every,
                                 &extra,
                                 &last_tick,
+                                &time_zone,
+                                &misfire,
+                                &misfire_max_runs,
+                                &misfire_grace_period_ms,
+                                &next_tick_nanos,
+                                &last_tick_nanos,
+                                &repeated_every_nanos,
+                                &overlap_policy,
+                                &timeout_ms,
+                                &retry_max_attempts,
+                                &retry_initial_backoff_ms,
+                                &retry_multiplier,
+                                &retry_max_backoff_ms,
+                                &retry_jitter,
+                                &retry_attempt,
+                                &retry_tick,
+                                &retry_tick_nanos,
+                                &concurrency_group,
+                                &name,
+                                &description,
+                                &tags,
+                                &job_kind,
                             ],
                         )
                         .await;
@@ -188,11 +288,7 @@ impl DataStore<JobStoredData> for PostgresMetadataStore {
 
 impl From<Row> for JobStoredData {
     fn from(row: Row) -> Self {
-        /*
-        id, last_updated, next_tick, last_tick, job_type, count, \
-                        ran, stopped, schedule, repeating, repeated_every, \
-                        extra
-         */
+        // The row has the columns of JOB_COLUMNS
         let id: Uuid = row.get(0);
         let last_updated = row.try_get(1).ok().map(|i: i64| i as u64);
         let next_tick = row
@@ -213,7 +309,13 @@ impl From<Row> for JobStoredData {
             let job_type = JobType::from_i32(job_type);
             match job_type {
                 Some(JobType::Cron) => match row.try_get(8) {
-                    Ok(schedule) => Some(CronJobType(CronJob { schedule })),
+                    Ok(schedule) => {
+                        let time_zone: Option<String> = row.try_get(12).unwrap_or_default();
+                        Some(CronJobType(CronJob {
+                            schedule,
+                            time_zone: time_zone.unwrap_or_default(),
+                        }))
+                    }
                     _ => None,
                 },
                 Some(_) => {
@@ -223,15 +325,37 @@ impl From<Row> for JobStoredData {
                         .ok()
                         .map(|i: i64| i as u64)
                         .unwrap_or_default();
+                    let repeated_every_nanos: Option<i32> = row.try_get(18).unwrap_or_default();
                     Some(NonCronJobType(NonCronJob {
                         repeating,
                         repeated_every,
+                        repeated_every_nanos: repeated_every_nanos.unwrap_or_default() as u32,
                     }))
                 }
                 None => None,
             }
         };
         let extra = row.try_get(11).unwrap_or_default();
+        let misfire: Option<i32> = row.try_get(13).unwrap_or_default();
+        let misfire_max_runs: Option<i32> = row.try_get(14).unwrap_or_default();
+        let misfire_grace_period_ms: Option<i64> = row.try_get(15).unwrap_or_default();
+        let next_tick_nanos: Option<i32> = row.try_get(16).unwrap_or_default();
+        let last_tick_nanos: Option<i32> = row.try_get(17).unwrap_or_default();
+        let overlap_policy: Option<i32> = row.try_get(19).unwrap_or_default();
+        let timeout_ms: Option<i64> = row.try_get(20).unwrap_or_default();
+        let retry_max_attempts: Option<i32> = row.try_get(21).unwrap_or_default();
+        let retry_initial_backoff_ms: Option<i64> = row.try_get(22).unwrap_or_default();
+        let retry_multiplier: Option<f64> = row.try_get(23).unwrap_or_default();
+        let retry_max_backoff_ms: Option<i64> = row.try_get(24).unwrap_or_default();
+        let retry_jitter: Option<f64> = row.try_get(25).unwrap_or_default();
+        let retry_attempt: Option<i32> = row.try_get(26).unwrap_or_default();
+        let retry_tick: Option<i64> = row.try_get(27).unwrap_or_default();
+        let retry_tick_nanos: Option<i32> = row.try_get(28).unwrap_or_default();
+        let concurrency_group: Option<String> = row.try_get(29).unwrap_or_default();
+        let name: Option<String> = row.try_get(30).unwrap_or_default();
+        let description: Option<String> = row.try_get(31).unwrap_or_default();
+        let tags: Option<Vec<String>> = row.try_get(32).unwrap_or_default();
+        let job_kind: Option<String> = row.try_get(33).unwrap_or_default();
 
         Self {
             id: Some(id.into()),
@@ -243,6 +367,26 @@ impl From<Row> for JobStoredData {
             extra,
             ran,
             stopped,
+            misfire: misfire.unwrap_or_default(),
+            misfire_max_runs: misfire_max_runs.unwrap_or_default() as u32,
+            misfire_grace_period_ms: misfire_grace_period_ms.unwrap_or_default() as u64,
+            next_tick_nanos: next_tick_nanos.unwrap_or_default() as u32,
+            last_tick_nanos: last_tick_nanos.unwrap_or_default() as u32,
+            overlap_policy: overlap_policy.unwrap_or_default(),
+            timeout_ms: timeout_ms.unwrap_or_default() as u64,
+            retry_max_attempts: retry_max_attempts.unwrap_or_default() as u32,
+            retry_initial_backoff_ms: retry_initial_backoff_ms.unwrap_or_default() as u64,
+            retry_multiplier: retry_multiplier.unwrap_or_default(),
+            retry_max_backoff_ms: retry_max_backoff_ms.unwrap_or_default() as u64,
+            retry_jitter: retry_jitter.unwrap_or_default(),
+            retry_attempt: retry_attempt.unwrap_or_default() as u32,
+            retry_tick: retry_tick.unwrap_or_default() as u64,
+            retry_tick_nanos: retry_tick_nanos.unwrap_or_default() as u32,
+            concurrency_group: concurrency_group.unwrap_or_default(),
+            name: name.unwrap_or_default(),
+            description: description.unwrap_or_default(),
+            tags: tags.unwrap_or_default(),
+            job_kind: job_kind.unwrap_or_default(),
             job,
         }
     }
@@ -280,6 +424,28 @@ impl InitStore for PostgresMetadataStore {
                                             repeating BOOL,\
                                             repeated_every BIGINT,\
                                             extra BYTEA,
+                                            time_zone TEXT,
+                                            misfire INTEGER,
+                                            misfire_max_runs INTEGER,
+                                            misfire_grace_period_ms BIGINT,
+                                            next_tick_nanos INTEGER,
+                                            last_tick_nanos INTEGER,
+                                            repeated_every_nanos INTEGER,
+                                            overlap_policy INTEGER,
+                                            timeout_ms BIGINT,
+                                            retry_max_attempts INTEGER,
+                                            retry_initial_backoff_ms BIGINT,
+                                            retry_multiplier DOUBLE PRECISION,
+                                            retry_max_backoff_ms BIGINT,
+                                            retry_jitter DOUBLE PRECISION,
+                                            retry_attempt INTEGER,
+                                            retry_tick BIGINT,
+                                            retry_tick_nanos INTEGER,
+                                            concurrency_group TEXT,
+                                            name TEXT,
+                                            description TEXT,
+                                            tags TEXT[],
+                                            job_kind TEXT,
                                             CONSTRAINT pk_metadata PRIMARY KEY (id)
                                         )";
                                 let create = v.execute(&*sql, &[]).await;
@@ -287,6 +453,21 @@ impl InitStore for PostgresMetadataStore {
                                     error!("Error on init Postgres Metadata store {:?}", e);
                                     return Err(JobSchedulerError::CantInit);
                                 }
+                                // Columns added after the table was first released
+                                for column in ADDED_COLUMNS {
+                                    let sql = "ALTER TABLE ".to_string()
+                                        + &*table
+                                        + " ADD COLUMN IF NOT EXISTS "
+                                        + column;
+                                    let alter = v.execute(&*sql, &[]).await;
+                                    if let Err(e) = alter {
+                                        error!(
+                                            "Error on migrating Postgres Metadata store {:?}",
+                                            e
+                                        );
+                                        return Err(JobSchedulerError::CantInit);
+                                    }
+                                }
                             }
                         }
                         *w = v;
@@ -315,6 +496,7 @@ impl InitStore for PostgresMetadataStore {
 impl MetaDataStorage for PostgresMetadataStore {
     fn list_next_ticks(
         &mut self,
+        now: DateTime<Utc>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<JobAndNextTick>, JobSchedulerError>> + Send>> {
         let store = self.store.clone();
         let table = self.table.clone();
@@ -325,16 +507,18 @@ impl MetaDataStorage for PostgresMetadataStore {
                 PostgresStore::Created(_) => Err(JobSchedulerError::CantListNextTicks),
                 PostgresStore::Inited(store) => {
                     let store = store.read().await;
-                    let now = Utc::now().timestamp();
+                    let now = now.timestamp();
                     let sql = "SELECT \
-                            id, job_type, next_tick, last_tick \
+                            id, job_type, next_tick, last_tick, next_tick_nanos, last_tick_nanos, \
+                            retry_tick, retry_tick_nanos, retry_attempt, stopped \
                         FROM "
                         .to_string()
                         + &*table
                         + " \
                         WHERE \
-                              next_tick > 0 \
-                          AND next_tick < $1";
+                              NOT COALESCE(stopped, false) \
+                          AND ((next_tick > 0 AND next_tick <= $1) \
+                           OR (retry_tick > 0 AND retry_tick <= $1))";
                     let rows = store.query(&*sql, &[&now]).await;
                     match rows {
                         Ok(rows) => Ok(rows
@@ -349,12 +533,27 @@ impl MetaDataStorage for PostgresMetadataStore {
                                     .map(|i: i64| i as u64)
                                     .unwrap_or_default();
                                 let last_tick = row.try_get(3).ok().map(|i: i64| i as u64);
+                                let next_tick_nanos: Option<i32> =
+                                    row.try_get(4).unwrap_or_default();
+                                let last_tick_nanos: Option<i32> =
+                                    row.try_get(5).unwrap_or_default();
+                                let retry_tick: Option<i64> = row.try_get(6).unwrap_or_default();
+                                let retry_tick_nanos: Option<i32> =
+                                    row.try_get(7).unwrap_or_default();
+                                let retry_attempt: Option<i32> = row.try_get(8).unwrap_or_default();
+                                let stopped: Option<bool> = row.try_get(9).unwrap_or_default();
 
                                 JobAndNextTick {
                                     id: Some(id),
                                     job_type,
                                     next_tick,
                                     last_tick,
+                                    next_tick_nanos: next_tick_nanos.unwrap_or_default() as u32,
+                                    last_tick_nanos: last_tick_nanos.unwrap_or_default() as u32,
+                                    retry_tick: retry_tick.unwrap_or_default() as u64,
+                                    retry_tick_nanos: retry_tick_nanos.unwrap_or_default() as u32,
+                                    retry_attempt: retry_attempt.unwrap_or_default() as u32,
+                                    stopped: stopped.unwrap_or_default(),
                                 }
                             })
                             .collect::<Vec<_>>()),
@@ -383,16 +582,33 @@ impl MetaDataStorage for PostgresMetadataStore {
                 PostgresStore::Created(_) => Err(JobSchedulerError::UpdateJobData),
                 PostgresStore::Inited(store) => {
                     let store = store.read().await;
+                    let next_tick_nanos = next_tick
+                        .map(|b| b.timestamp_subsec_nanos() as i32)
+                        .unwrap_or(0);
                     let next_tick = next_tick.map(|b| b.timestamp()).unwrap_or(0);
+                    let last_tick_nanos = last_tick
+                        .map(|b| b.timestamp_subsec_nanos() as i32)
+                        .unwrap_or(0);
                     let last_tick = last_tick.map(|b| b.timestamp());
                     let sql = "UPDATE ".to_string()
                         + &*table
                         + " \
                         SET \
-                         next_tick=$1, last_tick=$2 \
+                         next_tick=$1, last_tick=$2, next_tick_nanos=$3, last_tick_nanos=$4 \
                         WHERE \
-                            id = $3";
-                    let resp = store.query(&sql, &[&next_tick, &last_tick, &guid]).await;
+                            id = $5";
+                    let resp = store
+                        .query(
+                            &sql,
+                            &[
+                                &next_tick,
+                                &last_tick,
+                                &next_tick_nanos,
+                                &last_tick_nanos,
+                                &guid,
+                            ],
+                        )
+                        .await;
                     if let Err(e) = resp {
                         error!("Error updating next and last tick {:?}", e);
                         Err(JobSchedulerError::UpdateJobData)
@@ -404,8 +620,50 @@ impl MetaDataStorage for PostgresMetadataStore {
         })
     }
 
+    fn set_retry(
+        &mut self,
+        guid: Uuid,
+        retry_tick: Option<DateTime<Utc>>,
+        attempt: u32,
+    ) -> Pin<Box<dyn Future<Output = Result<(), JobSchedulerError>> + Send>> {
+        let store = self.store.clone();
+        let table = self.table.clone();
+
+        Box::pin(async move {
+            let store = store.read().await;
+            match &*store {
+                PostgresStore::Created(_) => Err(JobSchedulerError::UpdateJobData),
+                PostgresStore::Inited(store) => {
+                    let store = store.read().await;
+                    let retry_tick_nanos = retry_tick
+                        .map(|b| b.timestamp_subsec_nanos() as i32)
+                        .unwrap_or(0);
+                    let retry_tick = retry_tick.map(|b| b.timestamp()).unwrap_or(0);
+                    let attempt = attempt as i32;
+                    let sql = "UPDATE ".to_string()
+                        + &*table
+                        + " \
+                        SET \
+                         retry_tick=$1, retry_tick_nanos=$2, retry_attempt=$3 \
+                        WHERE \
+                            id = $4";
+                    let resp = store
+                        .query(&sql, &[&retry_tick, &retry_tick_nanos, &attempt, &guid])
+                        .await;
+                    if let Err(e) = resp {
+                        error!("Error updating retry tick {:?}", e);
+                        Err(JobSchedulerError::UpdateJobData)
+                    } else {
+                        Ok(())
+                    }
+                }
+            }
+        })
+    }
+
     fn time_till_next_job(
         &mut self,
+        now: DateTime<Utc>,
     ) -> Pin<Box<dyn Future<Output = Result<Option<Duration>, JobSchedulerError>> + Send>> {
         let store = self.store.clone();
         let table = self.table.clone();
@@ -415,19 +673,24 @@ impl MetaDataStorage for PostgresMetadataStore {
                 PostgresStore::Created(_) => Err(JobSchedulerError::CouldNotGetTimeUntilNextTick),
                 PostgresStore::Inited(store) => {
                     let store = store.read().await;
-                    let now = Utc::now().timestamp();
-                    let sql = "SELECT \
-                            next_tick \
-                        FROM "
+                    let sql = "SELECT tick, nanos FROM (\
+                            SELECT next_tick AS tick, COALESCE(next_tick_nanos, 0) AS nanos \
+                            FROM "
                         .to_string()
                         + &*table
-                        + " \
+                        + " WHERE next_tick > 0 AND NOT COALESCE(stopped, false) \
+                            UNION ALL \
+                            SELECT retry_tick AS tick, COALESCE(retry_tick_nanos, 0) AS nanos \
+                            FROM "
+                        + &*table
+                        + " WHERE retry_tick > 0 AND NOT COALESCE(stopped, false) \
+                        ) ticks \
                         WHERE \
-                              next_tick > 0\
-                          AND next_tick > $2 \
-                        ORDER BY next_tick ASC \
+                              tick > $1 OR (tick = $1 AND nanos > $2) \
+                        ORDER BY tick ASC, nanos ASC \
                         LIMIT 1";
-                    let row = store.query(&*sql, &[&now]).await;
+                    let nanos = now.timestamp_subsec_nanos() as i32;
+                    let row = store.query(&*sql, &[&now.timestamp(), &nanos]).await;
                     if let Err(e) = row {
                         error!("Error getting time until next job {:?}", e);
                         return Err(JobSchedulerError::CouldNotGetTimeUntilNextTick);
@@ -435,11 +698,33 @@ impl MetaDataStorage for PostgresMetadataStore {
                     let row = row.unwrap();
                     Ok(row
                         .get(0)
-                        .map(|r| r.get::<_, i64>(0))
-                        .map(|ts| ts - now)
-                        .filter(|ts| *ts > 0)
-                        .map(|ts| ts as u64)
-                        .map(std::time::Duration::from_secs))
+                        .map(|r| (r.get::<_, i64>(0), r.get::<_, i32>(1)))
+                        .map(|(ts, nanos)| JobAndNextTick::utc_with_nanos(ts as u64, nanos as u32))
+                        .and_then(|next_tick| (next_tick - now).to_std().ok()))
+                }
+            }
+        })
+    }
+
+    fn list_jobs(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<JobStoredData>, JobSchedulerError>> + Send>> {
+        let store = self.store.clone();
+        let table = self.table.clone();
+        Box::pin(async move {
+            let store = store.read().await;
+            match &*store {
+                PostgresStore::Created(_) => Err(JobSchedulerError::CantListJobs),
+                PostgresStore::Inited(store) => {
+                    let store = store.read().await;
+                    let sql = "select ".to_string() + JOB_COLUMNS + " from " + &*table;
+                    match store.query(&*sql, &[]).await {
+                        Ok(rows) => Ok(rows.into_iter().map(|row| row.into()).collect()),
+                        Err(e) => {
+                            error!("Error listing jobs {:?}", e);
+                            Err(JobSchedulerError::CantListJobs)
+                        }
+                    }
                 }
             }
         })
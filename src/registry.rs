@@ -0,0 +1,114 @@
+use crate::context::Context;
+use crate::job::to_code::{JobCode, PinnedGetFuture, ToCode};
+use crate::job::{JobResult, JobToRunFallibleAsync};
+use crate::job_scheduler::JobsSchedulerLocked;
+use crate::simple::SimpleJobCode;
+use crate::store::MetaDataStorage;
+use crate::JobSchedulerError;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+type LockedJobKindMap =
+    Arc<std::sync::RwLock<HashMap<String, Arc<RwLock<Box<JobToRunFallibleAsync>>>>>>;
+
+///
+/// What `JobScheduler::handle_orphans` does with jobs in the metadata store that have no code
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OrphanPolicy {
+    /// Only log a warning
+    #[default]
+    Report,
+    /// Pause the jobs, they can be resumed once their code is there
+    Pause,
+    /// Remove the jobs
+    Delete,
+}
+
+///
+/// Job code that survives a restart. Code is registered under a kind, and a job that is loaded
+/// from a persistent store runs the code of the kind set with `Job::set_job_kind`. Jobs added in
+/// this process run the code they were created with.
+///
+/// The registry is cloned before it is handed to the scheduler, so code can be registered later
+/// on as well.
+/// ```rust,ignore
+/// let registry = JobRegistry::default();
+/// registry.register("send-report", |_uuid, _l| Box::pin(async move {
+///     send_report().await?;
+///     Ok(())
+/// }));
+/// let sched = JobScheduler::new_with_storage_and_code(
+///     Box::new(metadata_storage),
+///     Box::new(notification_storage),
+///     Box::new(registry.clone()),
+///     Box::new(SimpleNotificationCode::default()),
+/// ).await?;
+/// ```
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    kinds: LockedJobKindMap,
+    jobs: SimpleJobCode,
+    storage: Option<Arc<RwLock<Box<dyn MetaDataStorage + Send + Sync>>>>,
+}
+
+impl JobRegistry {
+    ///
+    /// Register the code of a kind of job, replacing the code the kind had
+    pub fn register<T>(&self, kind: &str, run: T)
+    where
+        T: 'static,
+        T: FnMut(Uuid, JobsSchedulerLocked) -> Pin<Box<dyn Future<Output = JobResult> + Send>>
+            + Send
+            + Sync,
+    {
+        let run: Box<JobToRunFallibleAsync> = Box::new(run);
+        let mut w = self.kinds.write().unwrap_or_else(|e| e.into_inner());
+        w.insert(kind.to_string(), Arc::new(RwLock::new(run)));
+    }
+
+    ///
+    /// Whether code is registered for the kind
+    pub fn is_registered(&self, kind: &str) -> bool {
+        let r = self.kinds.read().unwrap_or_else(|e| e.into_inner());
+        r.contains_key(kind)
+    }
+}
+
+impl ToCode<Box<JobToRunFallibleAsync>> for JobRegistry {
+    fn init(
+        &mut self,
+        context: &Context,
+    ) -> Pin<Box<dyn Future<Output = Result<(), JobSchedulerError>> + Send>> {
+        self.storage = Some(context.metadata_storage.clone());
+        self.jobs.init(context)
+    }
+
+    fn get(&mut self, uuid: Uuid) -> PinnedGetFuture<Box<JobToRunFallibleAsync>> {
+        let job = self.jobs.get(uuid);
+        let kinds = self.kinds.clone();
+        let storage = self.storage.clone();
+        Box::pin(async move {
+            if let Some(job) = job.await? {
+                return Ok(Some(job));
+            }
+            let Some(storage) = storage else {
+                return Ok(None);
+            };
+            let job_data = {
+                let mut w = storage.write().await;
+                w.get(uuid).await?
+            };
+            let r = kinds.read().unwrap_or_else(|e| e.into_inner());
+            Ok(job_data
+                .as_ref()
+                .and_then(|job_data| job_data.job_kind())
+                .and_then(|kind| r.get(kind).cloned()))
+        })
+    }
+}
+
+impl JobCode for JobRegistry {}
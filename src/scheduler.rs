@@ -1,19 +1,25 @@
-use crate::context::Context;
+use crate::context::{Context, JobActivation};
 #[cfg(not(feature = "has_bytes"))]
 use crate::job::job_data::{JobState, JobType};
 #[cfg(feature = "has_bytes")]
 use crate::job::job_data_prost::{JobState, JobType};
+use crate::job::misfire::JobTicks;
 use crate::JobSchedulerError;
-use chrono::Utc;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::oneshot::{Receiver, Sender};
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use tracing::error;
 use uuid::Uuid;
 
+/// How long the scheduler sleeps at most before checking the store for changes made outside of
+/// this scheduler
+pub const DEFAULT_POLLING_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct Scheduler {
     pub shutdown: Arc<RwLock<bool>>,
+    pub polling_interval: Arc<RwLock<Duration>>,
+    pub wake: Option<Arc<Notify>>,
     pub start_tx: Arc<RwLock<Option<Sender<bool>>>>,
     pub start_rx: Arc<RwLock<Option<Receiver<bool>>>>,
     pub ticking: Arc<RwLock<bool>>,
@@ -25,6 +31,8 @@ impl Default for Scheduler {
         let (ticker_tx, ticker_rx) = tokio::sync::oneshot::channel();
         Self {
             shutdown: Arc::new(RwLock::new(false)),
+            polling_interval: Arc::new(RwLock::new(DEFAULT_POLLING_INTERVAL)),
+            wake: None,
             inited: false,
             start_tx: Arc::new(RwLock::new(Some(ticker_tx))),
             start_rx: Arc::new(RwLock::new(Some(ticker_rx))),
@@ -40,12 +48,18 @@ impl Scheduler {
         }
 
         let job_activation_tx = context.job_activation_tx.clone();
+        let pending_work = context.pending_work.clone();
         let notify_tx = context.notify_tx.clone();
         let job_delete_tx = context.job_delete_tx.clone();
         let shutdown = self.shutdown.clone();
         let metadata_storage = context.metadata_storage.clone();
+        let jobs_changed = context.jobs_changed.clone();
+        let polling_interval = self.polling_interval.clone();
+        let clock = context.clock.clone();
+        let tasks = context.tasks.clone();
 
         self.inited = true;
+        self.wake = Some(jobs_changed.clone());
 
         let start_rx = {
             let mut w = self.start_rx.write().await;
@@ -56,7 +70,7 @@ impl Scheduler {
         };
 
         let ticking = self.ticking.clone();
-        tokio::spawn(async move {
+        context.tasks.clone().spawn_listener(async move {
             let is_ticking = {
                 let ticking = ticking.read().await;
                 *ticking
@@ -93,21 +107,30 @@ impl Scheduler {
                 if shutdown {
                     break 'next_tick;
                 }
-                tokio::time::sleep(Duration::from_millis(500)).await;
-                let now = Utc::now();
+                let now = clock.now();
                 let next_ticks = {
                     let mut w = metadata_storage.write().await;
-                    w.list_next_ticks().await
+                    w.list_next_ticks(now).await
                 };
                 if let Err(e) = next_ticks {
                     error!("Error with listing next ticks {:?}", e);
+                    let polling_interval = *polling_interval.read().await;
+                    tokio::time::sleep(polling_interval).await;
                     continue 'next_tick;
                 }
                 let mut next_ticks = next_ticks.unwrap();
+                // Paused jobs are left alone until they are resumed
+                next_ticks.retain(|n| !n.stopped);
                 let to_be_deleted = next_ticks.iter().filter_map(|v| {
                     v.id.as_ref()?;
-                    if v.next_tick == 0 {
-                        let id: Uuid = v.id.as_ref().unwrap().into();
+                    // Jobs that are being retried are kept until the retries are done, and jobs
+                    // are kept until their runs and notifications were handled
+                    let id: Uuid = v.id.as_ref().unwrap().into();
+                    if v.next_tick == 0
+                        && v.retry_tick == 0
+                        && v.retry_attempt == 0
+                        && !pending_work.contains(&id)
+                    {
                         Some(id)
                     } else {
                         None
@@ -115,14 +138,20 @@ impl Scheduler {
                 });
                 for uuid in to_be_deleted {
                     let tx = job_delete_tx.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = tx.send(uuid) {
+                    tasks.spawn_run(async move {
+                        if let Err(e) = tx.send(uuid).await {
                             error!("Error sending deletion {:?}", e);
                         }
                     });
                 }
 
-                next_ticks.retain(|n| n.next_tick != 0);
+                next_ticks.retain(|n| n.next_tick != 0 || n.retry_tick != 0);
+
+                let retries = next_ticks
+                    .iter()
+                    .filter(|n| n.retry_tick_utc().map(|t| t <= now).unwrap_or_default())
+                    .filter_map(|n| n.id.as_ref().map(|id| id.into()))
+                    .collect::<Vec<Uuid>>();
 
                 let must_runs = next_ticks.iter().filter_map(|n| {
                     let next_tick = n.next_tick_utc();
@@ -163,49 +192,31 @@ impl Scheduler {
                         None
                     }
                 });
+                let must_runs = must_runs.collect::<Vec<_>>();
 
-                for uuid in must_runs {
-                    {
-                        let tx = notify_tx.clone();
-                        tokio::spawn(async move {
-                            if let Err(e) = tx.send((uuid, JobState::Scheduled)) {
-                                error!("Error sending notification activation {:?}", e);
-                            }
-                        });
-                    }
-                    {
-                        let tx = job_activation_tx.clone();
-                        tokio::spawn(async move {
-                            if let Err(e) = tx.send(uuid) {
-                                error!("Error sending job activation tx {:?}", e);
-                            }
-                        });
-                    }
-
-                    let storage = metadata_storage.clone();
-                    tokio::spawn(async move {
-                        let mut w = storage.write().await;
+                for uuid in must_runs.iter().copied() {
+                    let mut scheduled_at = now;
+                    let due = {
+                        let mut w = metadata_storage.write().await;
                         let job = w.get(uuid).await;
 
-                        let next_and_last_tick = match job {
+                        let mut retries_enabled = false;
+                        let due = match job {
                             Ok(Some(job)) => {
-                                let job_type: JobType = JobType::from_i32(job.job_type).unwrap();
+                                retries_enabled = job.retry_policy().is_some();
                                 let schedule = job.schedule();
-                                let repeated_every = job.repeated_every();
-                                let next_tick = job.next_tick_utc();
-                                let next_tick = match job_type {
-                                    JobType::Cron => schedule.and_then(|s| s.after(&now).next()),
-                                    JobType::OneShot => None,
-                                    JobType::Repeated => repeated_every.and_then(|r| {
-                                        next_tick.and_then(|nt| {
-                                            nt.checked_add_signed(chrono::Duration::seconds(
-                                                r as i64,
-                                            ))
-                                        })
-                                    }),
-                                };
-                                let last_tick = Some(now);
-                                Some((next_tick, last_tick))
+                                let time_zone = job.time_zone();
+                                job.next_tick_utc().map(|next_tick| {
+                                    scheduled_at = next_tick;
+                                    JobTicks {
+                                        job_type: job.job_type(),
+                                        schedule: schedule.as_ref(),
+                                        time_zone: time_zone.as_ref(),
+                                        repeated_every: job.repeated_every_duration(),
+                                        next_tick,
+                                    }
+                                    .due(&now, job.misfire_policy().unwrap_or_default())
+                                })
                             }
                             _ => {
                                 error!("Could not get job metadata");
@@ -213,22 +224,159 @@ impl Scheduler {
                             }
                         };
 
-                        if let Some((next_tick, last_tick)) = next_and_last_tick {
-                            if let Err(e) =
-                                w.set_next_and_last_tick(uuid, next_tick, last_tick).await
+                        if let Some(due) = due.as_ref() {
+                            if let Err(e) = w
+                                .set_next_and_last_tick(uuid, due.next_tick, Some(now))
+                                .await
                             {
                                 error!("Could not set next and last tick {:?}", e);
                             }
+                            // A regular run starts over with the first attempt
+                            if retries_enabled && due.runs > 0 {
+                                if let Err(e) = w.set_retry(uuid, None, 1).await {
+                                    error!("Could not reset retries {:?}", e);
+                                }
+                            }
                         }
-                    });
+                        due
+                    };
+
+                    let Some(due) = due else {
+                        continue;
+                    };
+                    if due.missed {
+                        if let Err(e) = pending_work
+                            .send(
+                                uuid,
+                                &notify_tx,
+                                (uuid, JobState::Missed, Default::default()),
+                            )
+                            .await
+                        {
+                            error!("Error sending missed notification {:?}", e);
+                        }
+                    }
+                    for _ in 0..due.runs {
+                        if let Err(e) = pending_work
+                            .send(
+                                uuid,
+                                &notify_tx,
+                                (uuid, JobState::Scheduled, Default::default()),
+                            )
+                            .await
+                        {
+                            error!("Error sending notification activation {:?}", e);
+                        }
+                        let activation = JobActivation {
+                            job_id: uuid,
+                            scheduled_at,
+                            manual: false,
+                        };
+                        if let Err(e) = pending_work
+                            .send(uuid, &job_activation_tx, activation)
+                            .await
+                        {
+                            error!("Error sending job activation tx {:?}", e);
+                        }
+                    }
                 }
+
+                for uuid in retries {
+                    if must_runs.contains(&uuid) {
+                        continue;
+                    }
+                    let scheduled_at = {
+                        let mut w = metadata_storage.write().await;
+                        let (attempt, retry_tick) = match w.get(uuid).await {
+                            Ok(Some(job)) => (job.retry_attempt, job.retry_tick_utc()),
+                            _ => {
+                                error!("Could not get job metadata");
+                                continue;
+                            }
+                        };
+                        if let Err(e) = w.set_retry(uuid, None, attempt).await {
+                            error!("Could not clear retry tick {:?}", e);
+                            continue;
+                        }
+                        retry_tick.unwrap_or(now)
+                    };
+                    if let Err(e) = pending_work
+                        .send(
+                            uuid,
+                            &notify_tx,
+                            (uuid, JobState::Scheduled, Default::default()),
+                        )
+                        .await
+                    {
+                        error!("Error sending notification activation {:?}", e);
+                    }
+                    let activation = JobActivation {
+                        job_id: uuid,
+                        scheduled_at,
+                        manual: false,
+                    };
+                    if let Err(e) = pending_work
+                        .send(uuid, &job_activation_tx, activation)
+                        .await
+                    {
+                        error!("Error sending job activation tx {:?}", e);
+                    }
+                }
+
+                // Sleep until the next job is due, or until jobs were added or removed
+                let polling_interval = *polling_interval.read().await;
+                let time_till_next_job = {
+                    let mut w = metadata_storage.write().await;
+                    w.time_till_next_job(clock.now()).await
+                };
+                let sleep_for = match time_till_next_job {
+                    Ok(Some(till_next)) => till_next.min(polling_interval),
+                    Ok(None) => polling_interval,
+                    Err(e) => {
+                        error!("Error getting time till next job {:?}", e);
+                        polling_interval
+                    }
+                };
+                let _woken = tokio::time::timeout(sleep_for, jobs_changed.notified()).await;
             }
         });
     }
 
     pub async fn shutdown(&mut self) {
-        let mut w = self.shutdown.write().await;
-        *w = true;
+        {
+            let mut w = self.shutdown.write().await;
+            *w = true;
+        }
+        if let Some(wake) = self.wake.as_ref() {
+            wake.notify_one();
+        }
+    }
+
+    /// Whether the tick loop was shut down
+    pub async fn is_shut_down(&self) -> bool {
+        *self.shutdown.read().await
+    }
+
+    /// Gets the scheduler ready to be initialized and started again after it was shut down. The
+    /// polling interval is kept.
+    pub fn reset(&mut self) {
+        let (start_tx, start_rx) = tokio::sync::oneshot::channel();
+        *self = Self {
+            polling_interval: self.polling_interval.clone(),
+            start_tx: Arc::new(RwLock::new(Some(start_tx))),
+            start_rx: Arc::new(RwLock::new(Some(start_rx))),
+            ..Default::default()
+        };
+    }
+
+    pub async fn set_polling_interval(&mut self, polling_interval: Duration) {
+        {
+            let mut w = self.polling_interval.write().await;
+            *w = polling_interval;
+        }
+        if let Some(wake) = self.wake.as_ref() {
+            wake.notify_one();
+        }
     }
 
     pub async fn start(&mut self) -> Result<(), JobSchedulerError> {
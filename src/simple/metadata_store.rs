@@ -81,18 +81,24 @@ impl InitStore for SimpleMetadataStore {
 impl MetaDataStorage for SimpleMetadataStore {
     fn list_next_ticks(
         &mut self,
+        _now: DateTime<Utc>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<JobAndNextTick>, JobSchedulerError>> + Send>> {
         let data = self.data.clone();
         Box::pin(async move {
             let r = data.read().await;
             let ret = r
-                .iter()
-                .map(|(_, v)| (v.id.clone(), v.next_tick, v.last_tick, v.job_type))
-                .map(|(id, next_tick, last_tick, job_type)| JobAndNextTick {
-                    id,
-                    next_tick,
-                    last_tick,
-                    job_type,
+                .values()
+                .map(|v| JobAndNextTick {
+                    id: v.id.clone(),
+                    next_tick: v.next_tick,
+                    last_tick: v.last_tick,
+                    job_type: v.job_type,
+                    next_tick_nanos: v.next_tick_nanos,
+                    last_tick_nanos: v.last_tick_nanos,
+                    retry_tick: v.retry_tick,
+                    retry_tick_nanos: v.retry_tick_nanos,
+                    retry_attempt: v.retry_attempt,
+                    stopped: v.stopped,
                 })
                 .collect::<Vec<_>>();
             Ok(ret)
@@ -120,31 +126,54 @@ impl MetaDataStorage for SimpleMetadataStore {
         })
     }
 
+    fn set_retry(
+        &mut self,
+        guid: Uuid,
+        retry_tick: Option<DateTime<Utc>>,
+        attempt: u32,
+    ) -> Pin<Box<dyn Future<Output = Result<(), JobSchedulerError>> + Send>> {
+        let data = self.data.clone();
+        Box::pin(async move {
+            let mut w = data.write().await;
+            let val = w.get_mut(&guid);
+            match val {
+                Some(val) => {
+                    val.set_retry_tick(retry_tick);
+                    val.retry_attempt = attempt;
+                    Ok(())
+                }
+                None => Err(JobSchedulerError::UpdateJobData),
+            }
+        })
+    }
+
     fn time_till_next_job(
         &mut self,
+        now: DateTime<Utc>,
     ) -> Pin<Box<dyn Future<Output = Result<Option<std::time::Duration>, JobSchedulerError>> + Send>>
     {
         let data = self.data.clone();
         Box::pin(async move {
             let r = data.read().await;
-            let now = Utc::now();
-            let now = now.timestamp() as u64;
             let val = r
-                .iter()
-                .filter_map(|(_, jd)| match jd.next_tick {
-                    0 => None,
-                    i => {
-                        if i > now {
-                            Some(i)
-                        } else {
-                            None
-                        }
-                    }
-                })
+                .values()
+                .filter(|jd| !jd.stopped)
+                .flat_map(|jd| [jd.next_tick_utc(), jd.retry_tick_utc()])
+                .flatten()
+                .filter(|next_tick| *next_tick > now)
                 .min()
-                .map(|t| t - now)
-                .map(std::time::Duration::from_secs);
+                .and_then(|next_tick| (next_tick - now).to_std().ok());
             Ok(val)
         })
     }
+
+    fn list_jobs(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<JobStoredData>, JobSchedulerError>> + Send>> {
+        let data = self.data.clone();
+        Box::pin(async move {
+            let r = data.read().await;
+            Ok(r.values().cloned().collect())
+        })
+    }
 }
@@ -1,24 +1,26 @@
+use crate::channel::FanOut;
 use crate::context::{Context, NotificationDeletedResult};
 #[cfg(not(feature = "has_bytes"))]
 use crate::job::job_data::{JobIdAndNotification, JobState, NotificationData};
 #[cfg(feature = "has_bytes")]
 use crate::job::job_data_prost::{JobIdAndNotification, JobState, NotificationData};
 use crate::job::to_code::{JobCode, NotificationCode, ToCode};
-use crate::job::JobToRunAsync;
+use crate::job::JobToRunFallibleAsync;
 use crate::{JobSchedulerError, JobStoredData, OnJobNotification};
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::broadcast::{Receiver, Sender};
+use tokio::sync::mpsc::Receiver;
 use tokio::sync::RwLock;
 use tracing::{error, warn};
 use uuid::Uuid;
 
-pub type LockedJobToRunMap = Arc<RwLock<HashMap<Uuid, Arc<RwLock<Box<JobToRunAsync>>>>>>;
+pub type LockedJobToRunMap = Arc<RwLock<HashMap<Uuid, Arc<RwLock<Box<JobToRunFallibleAsync>>>>>>;
 pub type LockedNotificationToRunMap =
     Arc<RwLock<HashMap<Uuid, Arc<RwLock<Box<OnJobNotification>>>>>>;
 
+#[derive(Clone)]
 pub struct SimpleJobCode {
     pub job_code: LockedJobToRunMap,
 }
@@ -34,15 +36,9 @@ impl Default for SimpleJobCode {
 impl SimpleJobCode {
     async fn listen_for_additions(
         data: LockedJobToRunMap,
-        mut rx: Receiver<(JobStoredData, Arc<RwLock<Box<JobToRunAsync>>>)>,
+        mut rx: Receiver<(JobStoredData, Arc<RwLock<Box<JobToRunFallibleAsync>>>)>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving {:?}", e);
-                break;
-            }
-            let (JobStoredData { id: job_id, .. }, val) = val.unwrap();
+        while let Some((JobStoredData { id: job_id, .. }, val)) = rx.recv().await {
             let uuid: Uuid = job_id.as_ref().unwrap().into();
             let mut w = data.write().await;
             w.insert(uuid, val);
@@ -53,13 +49,7 @@ impl SimpleJobCode {
         data: LockedJobToRunMap,
         mut rx: Receiver<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving job removal {:?}", e);
-                break;
-            }
-            let uuid = val.unwrap();
+        while let Some(uuid) = rx.recv().await {
             if let Ok(uuid) = uuid {
                 let mut w = data.write().await;
                 w.remove(&uuid);
@@ -68,7 +58,7 @@ impl SimpleJobCode {
     }
 }
 
-impl ToCode<Box<JobToRunAsync>> for SimpleJobCode {
+impl ToCode<Box<JobToRunFallibleAsync>> for SimpleJobCode {
     fn init(
         &mut self,
         context: &Context,
@@ -76,13 +66,14 @@ impl ToCode<Box<JobToRunAsync>> for SimpleJobCode {
         let data = self.job_code.clone();
         let job_create = context.job_create_tx.subscribe();
         let job_deleted = context.job_deleted_tx.subscribe();
+        let tasks = context.tasks.clone();
 
         Box::pin(async move {
-            tokio::spawn(SimpleJobCode::listen_for_additions(
+            tasks.spawn_listener(SimpleJobCode::listen_for_additions(
                 data.clone(),
                 job_create,
             ));
-            tokio::spawn(SimpleJobCode::listen_for_removals(data, job_deleted));
+            tasks.spawn_listener(SimpleJobCode::listen_for_removals(data, job_deleted));
             Ok(())
         })
     }
@@ -92,8 +83,12 @@ impl ToCode<Box<JobToRunAsync>> for SimpleJobCode {
         uuid: Uuid,
     ) -> Pin<
         Box<
-            dyn Future<Output = Result<Option<Arc<RwLock<Box<JobToRunAsync>>>>, JobSchedulerError>>
-                + Send,
+            dyn Future<
+                    Output = Result<
+                        Option<Arc<RwLock<Box<JobToRunFallibleAsync>>>>,
+                        JobSchedulerError,
+                    >,
+                > + Send,
         >,
     > {
         let data = self.job_code.clone();
@@ -122,15 +117,9 @@ impl SimpleNotificationCode {
     async fn listen_for_additions(
         data: LockedNotificationToRunMap,
         mut rx: Receiver<(NotificationData, Arc<RwLock<Box<OnJobNotification>>>)>,
-        tx: Sender<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
+        tx: FanOut<Result<Uuid, (JobSchedulerError, Option<Uuid>)>>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving {:?}", e);
-                break;
-            }
-            let (uuid, val) = val.unwrap();
+        while let Some((uuid, val)) = rx.recv().await {
             let uuid: Uuid = {
                 match uuid {
                     NotificationData {
@@ -148,7 +137,7 @@ impl SimpleNotificationCode {
                 let mut w = data.write().await;
                 w.insert(uuid, val);
             }
-            if let Err(e) = tx.send(Ok(uuid)) {
+            if let Err(e) = tx.send(Ok(uuid)).await {
                 warn!("Error sending notification created {:?} {:?}", e, uuid);
             }
         }
@@ -158,15 +147,9 @@ impl SimpleNotificationCode {
     async fn listen_for_removals(
         data: LockedNotificationToRunMap,
         mut rx: Receiver<(Uuid, Option<Vec<JobState>>)>,
-        tx: Sender<NotificationDeletedResult>,
+        tx: FanOut<NotificationDeletedResult>,
     ) {
-        loop {
-            let val = rx.recv().await;
-            if let Err(e) = val {
-                error!("Error receiving job removal {:?}", e);
-                break;
-            }
-            let (uuid, states) = val.unwrap();
+        while let Some((uuid, states)) = rx.recv().await {
             error!(
                 "Removing notification uuid {:?} and not caring about states!",
                 uuid
@@ -175,7 +158,7 @@ impl SimpleNotificationCode {
                 let mut w = data.write().await;
                 w.remove(&uuid);
             }
-            if let Err(e) = tx.send(Ok((uuid, true, states))) {
+            if let Err(e) = tx.send(Ok((uuid, true, states))).await {
                 error!("Error sending notification removed {:?} {:?}", e, uuid)
             }
         }
@@ -192,14 +175,15 @@ impl ToCode<Box<OnJobNotification>> for SimpleNotificationCode {
         let tx_created = context.notify_created_tx.clone();
         let rx_delete = context.notify_delete_tx.subscribe();
         let tx_deleted = context.notify_deleted_tx.clone();
+        let tasks = context.tasks.clone();
 
         Box::pin(async move {
-            tokio::spawn(SimpleNotificationCode::listen_for_additions(
+            tasks.spawn_listener(SimpleNotificationCode::listen_for_additions(
                 data.clone(),
                 rx_create,
                 tx_created,
             ));
-            tokio::spawn(SimpleNotificationCode::listen_for_removals(
+            tasks.spawn_listener(SimpleNotificationCode::listen_for_removals(
                 data, rx_delete, tx_deleted,
             ));
             Ok(())
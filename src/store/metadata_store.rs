@@ -2,7 +2,7 @@
 use crate::job::job_data::{JobAndNextTick, JobStoredData};
 #[cfg(feature = "has_bytes")]
 use crate::job::job_data_prost::{JobAndNextTick, JobStoredData};
-use crate::job::JobToRunAsync;
+use crate::job::JobToRunFallibleAsync;
 use crate::store::{CodeGet, DataStore, InitStore};
 use crate::JobSchedulerError;
 use chrono::{DateTime, Utc};
@@ -11,8 +11,10 @@ use std::pin::Pin;
 use uuid::Uuid;
 
 pub trait MetaDataStorage: DataStore<JobStoredData> + InitStore {
+    /// The ticks of the jobs, at least of the ones that are due at `now`
     fn list_next_ticks(
         &mut self,
+        now: DateTime<Utc>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<JobAndNextTick>, JobSchedulerError>> + Send>>;
     fn set_next_and_last_tick(
         &mut self,
@@ -20,9 +22,22 @@ pub trait MetaDataStorage: DataStore<JobStoredData> + InitStore {
         next_tick: Option<DateTime<Utc>>,
         last_tick: Option<DateTime<Utc>>,
     ) -> Pin<Box<dyn Future<Output = Result<(), JobSchedulerError>> + Send>>;
+    /// Sets when the job is retried, `None` when there's no retry pending, and which attempt it is
+    fn set_retry(
+        &mut self,
+        guid: Uuid,
+        retry_tick: Option<DateTime<Utc>>,
+        attempt: u32,
+    ) -> Pin<Box<dyn Future<Output = Result<(), JobSchedulerError>> + Send>>;
+    /// How long it is from `now` until the next tick or retry of a job that isn't paused
     fn time_till_next_job(
         &mut self,
+        now: DateTime<Utc>,
     ) -> Pin<Box<dyn Future<Output = Result<Option<std::time::Duration>, JobSchedulerError>> + Send>>;
+    /// All the jobs in the store, in no particular order
+    fn list_jobs(
+        &mut self,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<JobStoredData>, JobSchedulerError>> + Send>>;
 }
 
-pub trait JobCodeGet: CodeGet<Box<JobToRunAsync>> {}
+pub trait JobCodeGet: CodeGet<Box<JobToRunFallibleAsync>> {}
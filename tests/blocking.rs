@@ -0,0 +1,34 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_timed_out_sync_job_keeps_its_place_until_it_is_done() {
+    let sched = JobScheduler::builder()
+        .max_blocking_jobs(1)
+        .build()
+        .await
+        .unwrap();
+    let finished_at = Arc::new(Mutex::new(None));
+    let finished = finished_at.clone();
+    let mut slow = Job::new_one_shot(Duration::from_millis(100), move |_uuid, _l| {
+        std::thread::sleep(Duration::from_secs(1));
+        *finished.lock().unwrap() = Some(Instant::now());
+    })
+    .unwrap();
+    slow.set_timeout(Some(Duration::from_millis(100))).unwrap();
+    let started_at = Arc::new(Mutex::new(None));
+    let started = started_at.clone();
+    let next = Job::new_one_shot(Duration::from_millis(500), move |_uuid, _l| {
+        *started.lock().unwrap() = Some(Instant::now());
+    })
+    .unwrap();
+    sched.add(slow).await.unwrap();
+    sched.add(next).await.unwrap();
+    sched.start().await.unwrap();
+
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    let finished_at = finished_at.lock().unwrap().expect("Slow job didn't finish");
+    let started_at = started_at.lock().unwrap().expect("Next job didn't run");
+    assert!(started_at >= finished_at);
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_cron_scheduler::{Clock, Job, JobScheduler, MisfirePolicy};
+
+///
+/// A clock that only moves when it is told to
+struct ManualClock(Mutex<DateTime<Utc>>);
+
+impl ManualClock {
+    fn new(now: DateTime<Utc>) -> Arc<Self> {
+        Arc::new(Self(Mutex::new(now)))
+    }
+
+    fn advance(&self, by: Duration) {
+        let mut now = self.0.lock().unwrap();
+        *now += chrono::Duration::from_std(by).unwrap();
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
+}
+
+fn counting_one_shot(after: Duration, runs: &Arc<AtomicUsize>) -> Job {
+    let runs = runs.clone();
+    Job::new_one_shot_async(after, move |_uuid, _l| {
+        let runs = runs.clone();
+        Box::pin(async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+        })
+    })
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn the_first_tick_of_a_job_comes_from_the_clock_of_the_scheduler() {
+    let clock = ManualClock::new(Utc::now() - chrono::Duration::days(1));
+    let mut sched = JobScheduler::builder()
+        .polling_interval(Duration::from_millis(100))
+        .clock(clock.clone())
+        .build()
+        .await
+        .unwrap();
+    let runs = Arc::new(AtomicUsize::new(0));
+    sched
+        .add(counting_one_shot(Duration::from_secs(10), &runs))
+        .await
+        .unwrap();
+    sched.start().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+    clock.advance(Duration::from_secs(10));
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    sched.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_job_that_sets_the_default_misfire_policy_keeps_it() {
+    let clock = ManualClock::new(Utc::now());
+    let mut sched = JobScheduler::builder()
+        .polling_interval(Duration::from_millis(100))
+        .misfire_policy(MisfirePolicy::Skip)
+        .clock(clock.clone())
+        .build()
+        .await
+        .unwrap();
+    let fire_once = Arc::new(AtomicUsize::new(0));
+    let mut job = counting_one_shot(Duration::from_secs(10), &fire_once);
+    job.set_misfire_policy(MisfirePolicy::FireOnce).unwrap();
+    sched.add(job).await.unwrap();
+    let skipped = Arc::new(AtomicUsize::new(0));
+    sched
+        .add(counting_one_shot(Duration::from_secs(10), &skipped))
+        .await
+        .unwrap();
+    sched.start().await.unwrap();
+
+    // Both ticks are missed by more than the misfire threshold
+    clock.advance(Duration::from_secs(30));
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(fire_once.load(Ordering::SeqCst), 1);
+    assert_eq!(skipped.load(Ordering::SeqCst), 0);
+    sched.shutdown().await.unwrap();
+}
@@ -0,0 +1,47 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_cron_scheduler::{Job, JobNotification, JobScheduler};
+
+///
+/// The notifications a job sent, in the order they ran
+#[derive(Clone, Default)]
+pub struct Notifications(Arc<Mutex<Vec<JobNotification>>>);
+
+impl Notifications {
+    /// Records the notifications the job sends for the states
+    pub async fn of(sched: &JobScheduler, job: &Job, states: Vec<JobNotification>) -> Self {
+        let notifications = Self::default();
+        let seen = notifications.0.clone();
+        job.on_notifications_add(
+            sched,
+            Box::new(move |_job_id, _notification_id, state| {
+                seen.lock().unwrap().push(state);
+                Box::pin(async {})
+            }),
+            states,
+        )
+        .await
+        .unwrap();
+        notifications
+    }
+
+    pub fn count(&self, state: JobNotification) -> usize {
+        let seen = self.0.lock().unwrap();
+        seen.iter().filter(|s| **s == state).count()
+    }
+
+    /// Waits until the job sent `count` notifications for the state, fails after 5 seconds
+    pub async fn wait_for(&self, state: JobNotification, count: usize) {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while self.count(state) < count {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "Waited for {} {:?} notifications, got {:?}",
+                count,
+                state,
+                self.0.lock().unwrap()
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+}
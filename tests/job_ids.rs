@@ -0,0 +1,74 @@
+use std::time::Duration;
+use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError, SimpleMetadataStore};
+
+fn nightly_report() -> Job {
+    Job::new_repeated_async(Duration::from_secs(3600), |_uuid, _l| Box::pin(async {})).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_job_with_an_id_of_its_own_is_registered_once() {
+    let sched = JobScheduler::new().await.unwrap();
+    let id = Job::id_from_name("nightly-report");
+    for _ in 0..2 {
+        let job = nightly_report().with_id(id);
+        assert_eq!(sched.add_or_replace(job).await.unwrap(), id);
+    }
+    let jobs = sched.list_jobs().await.unwrap();
+    assert_eq!(jobs.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn the_id_of_a_job_with_notifications_can_not_change() {
+    let sched = JobScheduler::new().await.unwrap();
+    let mut job = nightly_report();
+    job.on_start_notification_add(
+        &sched,
+        Box::new(|_job_id, _notification_id, _state| Box::pin(async {})),
+    )
+    .await
+    .unwrap();
+    let id = job.guid();
+    assert!(matches!(
+        job.set_id(Job::id_from_name("nightly-report")),
+        Err(JobSchedulerError::CantChangeId)
+    ));
+    assert_eq!(job.guid(), id);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_replaced_job_keeps_its_last_tick_and_count_after_a_restart() {
+    // The store outlives the scheduler, like a persistent one would
+    let store = SimpleMetadataStore::default();
+    let data = store.data.clone();
+    let id = Job::id_from_name("every-second");
+    let every_second = || {
+        Job::new_repeated_async(Duration::from_secs(1), |_uuid, _l| Box::pin(async {}))
+            .unwrap()
+            .with_id(id)
+    };
+    let mut before_restart = JobScheduler::builder()
+        .metadata_storage(Box::new(store))
+        .build()
+        .await
+        .unwrap();
+    before_restart.add_or_replace(every_second()).await.unwrap();
+    before_restart.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    before_restart.shutdown().await.unwrap();
+    let ran = before_restart.get_job(id).await.unwrap().unwrap();
+    assert!(ran.count >= 2 && ran.last_tick.is_some());
+
+    let sched = JobScheduler::builder()
+        .metadata_storage(Box::new(SimpleMetadataStore {
+            data,
+            inited: false,
+        }))
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(sched.add_or_replace(every_second()).await.unwrap(), id);
+    let jobs = sched.list_jobs().await.unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].count, ran.count);
+    assert_eq!(jobs[0].last_tick, ran.last_tick);
+}
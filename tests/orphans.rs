@@ -0,0 +1,115 @@
+use std::time::Duration;
+use tokio_cron_scheduler::{
+    Context, Job, JobCode, JobRegistry, JobScheduler, JobSchedulerError, JobToRunFallibleAsync,
+    OrphanPolicy, PinnedGetFuture, SimpleMetadataStore, ToCode,
+};
+use uuid::Uuid;
+
+///
+/// Code that has no job, and fails to look one up the first `failures` times
+struct FlakyCode {
+    failures: usize,
+}
+
+impl ToCode<Box<JobToRunFallibleAsync>> for FlakyCode {
+    fn init(
+        &mut self,
+        _context: &Context,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), JobSchedulerError>> + Send>>
+    {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn get(&mut self, _uuid: Uuid) -> PinnedGetFuture<Box<JobToRunFallibleAsync>> {
+        let fail = self.failures > 0;
+        self.failures = self.failures.saturating_sub(1);
+        Box::pin(async move {
+            if fail {
+                Err(JobSchedulerError::FetchJob)
+            } else {
+                Ok(None)
+            }
+        })
+    }
+}
+
+impl JobCode for FlakyCode {}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn jobs_without_code_are_handled_when_the_scheduler_is_initialized() {
+    // The store outlives the scheduler that added the jobs, like a persistent one would
+    let store = SimpleMetadataStore::default();
+    let data = store.data.clone();
+    let before_restart = JobScheduler::builder()
+        .metadata_storage(Box::new(store))
+        .build()
+        .await
+        .unwrap();
+    let mut ids = Vec::new();
+    for kind in ["send-report", "retired"] {
+        let mut job =
+            Job::new_repeated_async(Duration::from_secs(3600), |_uuid, _l| Box::pin(async {}))
+                .unwrap();
+        job.set_job_kind(Some(kind)).unwrap();
+        ids.push(before_restart.add(job).await.unwrap());
+    }
+
+    let registry = JobRegistry::default();
+    registry.register("send-report", |_uuid, _l| Box::pin(async { Ok(()) }));
+    let sched = JobScheduler::builder()
+        .metadata_storage(Box::new(SimpleMetadataStore {
+            data,
+            inited: false,
+        }))
+        .job_code(Box::new(registry))
+        .orphan_policy(OrphanPolicy::Pause)
+        .build()
+        .await
+        .unwrap();
+    sched.start().await.unwrap();
+
+    let jobs = sched.list_jobs().await.unwrap();
+    let paused = |id| jobs.iter().find(|job| job.id == id).unwrap().stopped;
+    assert!(!paused(ids[0]));
+    assert!(paused(ids[1]));
+    // The orphan is only handled once, when the scheduler is initialized
+    assert_eq!(
+        sched.handle_orphans(OrphanPolicy::Report).await.unwrap(),
+        vec![ids[1]]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_failed_init_is_done_again_by_the_next_call() {
+    let store = SimpleMetadataStore::default();
+    let data = store.data.clone();
+    let before_restart = JobScheduler::builder()
+        .metadata_storage(Box::new(store))
+        .build()
+        .await
+        .unwrap();
+    let job =
+        Job::new_repeated_async(Duration::from_secs(3600), |_uuid, _l| Box::pin(async {})).unwrap();
+    let job_id = before_restart.add(job).await.unwrap();
+
+    let mut sched = JobScheduler::builder()
+        .metadata_storage(Box::new(SimpleMetadataStore {
+            data,
+            inited: false,
+        }))
+        .job_code(Box::new(FlakyCode { failures: 1 }))
+        .orphan_policy(OrphanPolicy::Pause)
+        .build()
+        .await
+        .unwrap();
+    assert!(matches!(
+        sched.init().await,
+        Err(JobSchedulerError::FetchJob)
+    ));
+    assert!(!sched.inited().await);
+
+    sched.init().await.unwrap();
+    assert!(sched.inited().await);
+    let job = sched.get_job(job_id).await.unwrap().unwrap();
+    assert!(job.stopped);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::Notifications;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio_cron_scheduler::{Job, JobNotification, JobScheduler, OverlapPolicy};
+use uuid::Uuid;
+
+///
+/// A job of which every run waits until the gate lets it through. It only runs when it's run with
+/// `run_now`.
+async fn gated_job(
+    sched: &JobScheduler,
+    gate: &Arc<Semaphore>,
+    policy: OverlapPolicy,
+) -> (Uuid, Notifications) {
+    let gate = gate.clone();
+    let mut job = Job::new_repeated_async(Duration::from_secs(3600), move |_uuid, _l| {
+        let gate = gate.clone();
+        Box::pin(async move {
+            gate.acquire().await.unwrap().forget();
+        })
+    })
+    .unwrap();
+    job.set_overlap_policy(policy).unwrap();
+    let notifications = Notifications::of(
+        sched,
+        &job,
+        vec![
+            JobNotification::Started,
+            JobNotification::Done,
+            JobNotification::Skipped,
+            JobNotification::Cancelled,
+        ],
+    )
+    .await;
+    let job_id = sched.add(job).await.unwrap();
+    sched.start().await.unwrap();
+    (job_id, notifications)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn skip_if_running_skips_a_run_while_the_previous_one_is_going() {
+    let sched = JobScheduler::new().await.unwrap();
+    let gate = Arc::new(Semaphore::new(0));
+    let (job_id, notifications) = gated_job(&sched, &gate, OverlapPolicy::SkipIfRunning).await;
+
+    sched.run_now(job_id).await.unwrap();
+    notifications.wait_for(JobNotification::Started, 1).await;
+    sched.run_now(job_id).await.unwrap();
+    notifications.wait_for(JobNotification::Skipped, 1).await;
+    gate.add_permits(1);
+    notifications.wait_for(JobNotification::Done, 1).await;
+
+    // The job runs again once the previous run is done
+    sched.run_now(job_id).await.unwrap();
+    notifications.wait_for(JobNotification::Started, 2).await;
+    gate.add_permits(1);
+    notifications.wait_for(JobNotification::Done, 2).await;
+    assert_eq!(notifications.count(JobNotification::Skipped), 1);
+    assert_eq!(notifications.count(JobNotification::Cancelled), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn queue_one_runs_one_queued_run_after_the_previous_one() {
+    let sched = JobScheduler::new().await.unwrap();
+    let gate = Arc::new(Semaphore::new(0));
+    let (job_id, notifications) = gated_job(&sched, &gate, OverlapPolicy::QueueOne).await;
+
+    sched.run_now(job_id).await.unwrap();
+    notifications.wait_for(JobNotification::Started, 1).await;
+    sched.run_now(job_id).await.unwrap();
+    sched.run_now(job_id).await.unwrap();
+    // The second run is queued, the third is skipped as one is queued already
+    notifications.wait_for(JobNotification::Skipped, 1).await;
+    assert_eq!(notifications.count(JobNotification::Started), 1);
+
+    gate.add_permits(1);
+    notifications.wait_for(JobNotification::Started, 2).await;
+    gate.add_permits(1);
+    notifications.wait_for(JobNotification::Done, 2).await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(notifications.count(JobNotification::Started), 2);
+    assert_eq!(notifications.count(JobNotification::Skipped), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancel_previous_cancels_the_run_that_is_going() {
+    let sched = JobScheduler::new().await.unwrap();
+    let gate = Arc::new(Semaphore::new(0));
+    let (job_id, notifications) = gated_job(&sched, &gate, OverlapPolicy::CancelPrevious).await;
+
+    sched.run_now(job_id).await.unwrap();
+    notifications.wait_for(JobNotification::Started, 1).await;
+    sched.run_now(job_id).await.unwrap();
+    notifications.wait_for(JobNotification::Cancelled, 1).await;
+    notifications.wait_for(JobNotification::Started, 2).await;
+    assert_eq!(notifications.count(JobNotification::Done), 0);
+
+    gate.add_permits(1);
+    notifications.wait_for(JobNotification::Done, 1).await;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(notifications.count(JobNotification::Cancelled), 1);
+    assert_eq!(notifications.count(JobNotification::Done), 1);
+    assert_eq!(notifications.count(JobNotification::Skipped), 0);
+}
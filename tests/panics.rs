@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio_cron_scheduler::{
+    Context, Job, JobCode, JobNotification, JobScheduler, JobSchedulerError, JobToRunFallibleAsync,
+    PinnedGetFuture, SimpleMetadataStore, SimpleNotificationCode, SimpleNotificationStore, ToCode,
+};
+use uuid::Uuid;
+
+///
+/// Code of which every job panics before it returns its future
+#[derive(Default)]
+struct PanickingCode;
+
+impl ToCode<Box<JobToRunFallibleAsync>> for PanickingCode {
+    fn init(
+        &mut self,
+        _context: &Context,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), JobSchedulerError>> + Send>>
+    {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn get(&mut self, _uuid: Uuid) -> PinnedGetFuture<Box<JobToRunFallibleAsync>> {
+        let code: Box<JobToRunFallibleAsync> =
+            Box::new(|_uuid, _l| panic!("No future for this job"));
+        Box::pin(async move { Ok(Some(Arc::new(RwLock::new(code)))) })
+    }
+}
+
+impl JobCode for PanickingCode {}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_job_that_panics_before_returning_its_future_is_reported() {
+    let sched = JobScheduler::new_with_storage_and_code(
+        Box::new(SimpleMetadataStore::default()),
+        Box::new(SimpleNotificationStore::default()),
+        Box::new(PanickingCode),
+        Box::new(SimpleNotificationCode::default()),
+    )
+    .await
+    .unwrap();
+
+    let states = Arc::new(Mutex::new(Vec::new()));
+    let job = Job::new_one_shot_async(Duration::from_millis(200), |_uuid, _l| Box::pin(async {}))
+        .unwrap();
+    let seen = states.clone();
+    job.on_notifications_add(
+        &sched,
+        Box::new(move |_job_id, _notification_id, state| {
+            seen.lock().unwrap().push(state);
+            Box::pin(async {})
+        }),
+        vec![
+            JobNotification::Started,
+            JobNotification::Done,
+            JobNotification::Panicked,
+        ],
+    )
+    .await
+    .unwrap();
+    sched.add(job).await.unwrap();
+    sched.start().await.unwrap();
+
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(
+        *states.lock().unwrap(),
+        vec![JobNotification::Started, JobNotification::Panicked]
+    );
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::Notifications;
+use std::time::Duration;
+use tokio_cron_scheduler::{Job, JobNotification, JobScheduler};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_paused_job_does_not_run_until_it_is_resumed() {
+    let sched = JobScheduler::new().await.unwrap();
+    let job =
+        Job::new_repeated_async(Duration::from_secs(1), |_uuid, _l| Box::pin(async {})).unwrap();
+    let notifications = Notifications::of(
+        &sched,
+        &job,
+        vec![JobNotification::Started, JobNotification::Stop],
+    )
+    .await;
+    let job_id = sched.add(job).await.unwrap();
+    sched.start().await.unwrap();
+    notifications.wait_for(JobNotification::Started, 1).await;
+
+    sched.pause(job_id).await.unwrap();
+    notifications.wait_for(JobNotification::Stop, 1).await;
+    // A tick that was due right before the pause may still start
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let paused_at = notifications.count(JobNotification::Started);
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    assert_eq!(notifications.count(JobNotification::Started), paused_at);
+
+    sched.resume(job_id).await.unwrap();
+    notifications
+        .wait_for(JobNotification::Started, paused_at + 1)
+        .await;
+    assert_eq!(notifications.count(JobNotification::Stop), 1);
+}
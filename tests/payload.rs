@@ -0,0 +1,52 @@
+#![cfg(feature = "serde")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_job_reads_its_payload_from_its_context() {
+    let sched = JobScheduler::new().await.unwrap();
+    let read = Arc::new(Mutex::new(None));
+    let payload = read.clone();
+    let job = Job::new_one_shot_with_context(Duration::from_millis(100), move |ctx| {
+        *payload.lock().unwrap() = Some(ctx.payload::<(String, u32)>().unwrap());
+        Box::pin(async { Ok(()) })
+    })
+    .unwrap()
+    .with_payload(&("customer-x".to_string(), 3))
+    .unwrap();
+    sched.add(job).await.unwrap();
+    sched.start().await.unwrap();
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(
+        *read.lock().unwrap(),
+        Some(Some(("customer-x".to_string(), 3)))
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_job_keeps_the_payload_it_was_created_with() {
+    let sched = JobScheduler::new().await.unwrap();
+    let job = Job::new_repeated_async(Duration::from_secs(3600), |_uuid, _l| Box::pin(async {}))
+        .unwrap()
+        .with_payload(&("customer-x".to_string(), 3))
+        .unwrap();
+    let with_payload = sched.add(job).await.unwrap();
+    let job =
+        Job::new_repeated_async(Duration::from_secs(3600), |_uuid, _l| Box::pin(async {})).unwrap();
+    let without_payload = sched.add(job).await.unwrap();
+
+    assert_eq!(
+        sched.payload::<(String, u32)>(with_payload).await.unwrap(),
+        Some(("customer-x".to_string(), 3))
+    );
+    assert_eq!(
+        sched
+            .payload::<(String, u32)>(without_payload)
+            .await
+            .unwrap(),
+        None
+    );
+}
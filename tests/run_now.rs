@@ -0,0 +1,55 @@
+mod common;
+
+use common::Notifications;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_cron_scheduler::{Job, JobNotification, JobNotificationDetails, JobScheduler};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn run_now_runs_a_job_besides_its_schedule() {
+    let mut sched = JobScheduler::new().await.unwrap();
+    let job =
+        Job::new_repeated_async(Duration::from_secs(3600), |_uuid, _l| Box::pin(async {})).unwrap();
+    let states = vec![
+        JobNotification::Scheduled,
+        JobNotification::Started,
+        JobNotification::Done,
+    ];
+    let notifications = Notifications::of(&sched, &job, states.clone()).await;
+    // Whether the notifications were about a manual run
+    let manual = Arc::new(Mutex::new(Vec::new()));
+    let seen = manual.clone();
+    job.on_notifications_add(
+        &sched,
+        Box::new(move |_job_id, _notification_id, state| {
+            let details = JobNotificationDetails::current().unwrap();
+            seen.lock().unwrap().push((state, details.manual));
+            Box::pin(async {})
+        }),
+        states,
+    )
+    .await
+    .unwrap();
+    let job_id = sched.add(job).await.unwrap();
+    sched.start().await.unwrap();
+    let next_tick = sched.next_tick_for_job(job_id).await.unwrap();
+
+    sched.run_now(job_id).await.unwrap();
+    notifications.wait_for(JobNotification::Done, 1).await;
+    // The other handler of the notifications runs alongside
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(notifications.count(JobNotification::Scheduled), 1);
+    assert_eq!(notifications.count(JobNotification::Started), 1);
+    let mut manual = manual.lock().unwrap().clone();
+    manual.sort_by_key(|(state, _)| *state as i32);
+    assert_eq!(
+        manual,
+        vec![
+            (JobNotification::Scheduled, true),
+            (JobNotification::Started, true),
+            (JobNotification::Done, true),
+        ]
+    );
+    // The schedule of the job is left as it was
+    assert_eq!(sched.next_tick_for_job(job_id).await.unwrap(), next_tick);
+}
@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_cron_scheduler::{Job, JobScheduler, OverlapPolicy};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_cut_off_job_that_skips_overlapping_runs_runs_again_after_a_restart() {
+    let mut sched = JobScheduler::new().await.unwrap();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    let mut job = Job::new_repeated_async(Duration::from_secs(1), move |_uuid, _l| {
+        let counter = counter.clone();
+        Box::pin(async move {
+            // Only the first run takes longer than the shutdown waits for
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            }
+        })
+    })
+    .unwrap();
+    job.set_overlap_policy(OverlapPolicy::SkipIfRunning)
+        .unwrap();
+    let job_id = sched.add(job).await.unwrap();
+    sched.start().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    let cut_off = sched
+        .shutdown_graceful(Duration::from_millis(200))
+        .await
+        .unwrap();
+    assert_eq!(cut_off, vec![job_id]);
+
+    sched.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    assert!(
+        runs.load(Ordering::SeqCst) >= 2,
+        "Job didn't run after the restart, {} runs",
+        runs.load(Ordering::SeqCst)
+    );
+    sched.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_stopped_job_that_skips_overlapping_runs_runs_again_after_a_start() {
+    let mut sched = JobScheduler::new().await.unwrap();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    let mut job = Job::new_repeated_async(Duration::from_secs(1), move |_uuid, _l| {
+        let counter = counter.clone();
+        Box::pin(async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        })
+    })
+    .unwrap();
+    job.set_overlap_policy(OverlapPolicy::SkipIfRunning)
+        .unwrap();
+    sched.add(job).await.unwrap();
+    sched.start().await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+    sched.stop(Duration::from_secs(5)).await.unwrap();
+    let stopped_at = runs.load(Ordering::SeqCst);
+    assert!(stopped_at >= 1);
+
+    sched.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    assert!(
+        runs.load(Ordering::SeqCst) > stopped_at,
+        "Job didn't run after the start, {} runs",
+        runs.load(Ordering::SeqCst)
+    );
+    sched.shutdown().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stop_cuts_off_the_runs_that_are_not_done_by_the_timeout() {
+    let mut sched = JobScheduler::new().await.unwrap();
+    let never_returns = Job::new_one_shot_async(Duration::from_millis(100), |_uuid, _l| {
+        Box::pin(std::future::pending())
+    })
+    .unwrap();
+    // A sync run can't be interrupted, it only gets its cancellation token cancelled
+    let ignores_cancellation = Job::new_one_shot(Duration::from_millis(100), |_uuid, _l| {
+        std::thread::sleep(Duration::from_secs(3));
+    })
+    .unwrap();
+    let mut job_ids = vec![
+        sched.add(never_returns).await.unwrap(),
+        sched.add(ignores_cancellation).await.unwrap(),
+    ];
+    sched.start().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let stopping = Instant::now();
+    let mut cut_off = tokio::time::timeout(
+        Duration::from_secs(2),
+        sched.stop(Duration::from_millis(200)),
+    )
+    .await
+    .expect("Stop didn't return")
+    .unwrap();
+    assert!(stopping.elapsed() < Duration::from_secs(1));
+    cut_off.sort();
+    job_ids.sort();
+    assert_eq!(cut_off, job_ids);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_stuck_job_does_not_keep_the_scheduler_from_starting_again() {
+    let mut sched = JobScheduler::new().await.unwrap();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = runs.clone();
+    let mut job = Job::new_repeated_async(Duration::from_secs(1), move |_uuid, _l| {
+        let counter = counter.clone();
+        Box::pin(async move {
+            // Only the first run gets stuck
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                std::future::pending::<()>().await;
+            }
+        })
+    })
+    .unwrap();
+    job.set_overlap_policy(OverlapPolicy::SkipIfRunning)
+        .unwrap();
+    let job_id = sched.add(job).await.unwrap();
+
+    for cycle in 1..=2 {
+        sched.start().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        let cut_off = sched.stop(Duration::from_millis(200)).await.unwrap();
+        if cycle == 1 {
+            assert_eq!(cut_off, vec![job_id]);
+        }
+    }
+    assert!(
+        runs.load(Ordering::SeqCst) >= 2,
+        "Job didn't run after the restart, {} runs",
+        runs.load(Ordering::SeqCst)
+    );
+}
@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+const JOBS: usize = 3000;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn thousands_of_jobs_added_at_once_all_run() {
+    let sched = JobScheduler::new().await.unwrap();
+    sched.start().await.unwrap();
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut adds = Vec::with_capacity(JOBS);
+    for _ in 0..JOBS {
+        let sched = sched.clone();
+        let runs = runs.clone();
+        adds.push(tokio::spawn(async move {
+            let job = Job::new_one_shot_async(Duration::from_secs(1), move |_uuid, _l| {
+                let runs = runs.clone();
+                Box::pin(async move {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                })
+            })?;
+            sched.add(job).await
+        }));
+    }
+    for add in adds {
+        add.await.unwrap().expect("Adding a job failed");
+    }
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+    while runs.load(Ordering::SeqCst) < JOBS && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(runs.load(Ordering::SeqCst), JOBS);
+
+    // The scheduler still takes work after the burst
+    let after = Arc::new(AtomicUsize::new(0));
+    let counter = after.clone();
+    let job = Job::new_one_shot_async(Duration::from_millis(100), move |_uuid, _l| {
+        let counter = counter.clone();
+        Box::pin(async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+    })
+    .unwrap();
+    sched.add(job).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(after.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn notifications_of_a_burst_of_jobs_all_arrive() {
+    const NOTIFIED_JOBS: usize = 1000;
+    let sched = JobScheduler::new().await.unwrap();
+    sched.start().await.unwrap();
+
+    let done = Arc::new(AtomicUsize::new(0));
+    let mut adds = Vec::with_capacity(NOTIFIED_JOBS);
+    for _ in 0..NOTIFIED_JOBS {
+        let sched = sched.clone();
+        let done = done.clone();
+        adds.push(tokio::spawn(async move {
+            let mut job =
+                Job::new_one_shot_async(Duration::from_secs(1), |_uuid, _l| Box::pin(async {}))?;
+            job.on_done_notification_add(
+                &sched,
+                Box::new(move |_job_id, _notification_id, _state| {
+                    let done = done.clone();
+                    Box::pin(async move {
+                        done.fetch_add(1, Ordering::SeqCst);
+                    })
+                }),
+            )
+            .await?;
+            sched.add(job).await
+        }));
+    }
+    for add in adds {
+        add.await.unwrap().expect("Adding a job failed");
+    }
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(60);
+    while done.load(Ordering::SeqCst) < NOTIFIED_JOBS && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(done.load(Ordering::SeqCst), NOTIFIED_JOBS);
+}